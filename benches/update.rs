@@ -0,0 +1,66 @@
+//! Measures update() on a generated 100k-line configuration file, counting heap allocations alongside
+//! wall time. Run with `cargo bench --bench update`.
+
+use confee::conf::Conf;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use std::{env, fs, thread};
+
+const KEYS: usize = 100_000;
+
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn measure(label: &str, conf: &mut Conf) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    conf.update().unwrap();
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!("{:<28} {:>10.2?} {:>10} allocations", label, elapsed, allocations);
+}
+
+fn run() {
+    let file_name = env::temp_dir().join("confee-bench-update.conf");
+    let contents: String = (0..KEYS).map(|i| format!("key{}: value{}\n", i, i % 100)).collect();
+    fs::write(&file_name, contents).unwrap();
+
+    let defaults: Vec<(String, String)> = (0..KEYS).map(|i| (format!("key{}", i), "default".to_string())).collect();
+    let defaults: Box<[(String, String); KEYS]> = defaults.into_boxed_slice().try_into().unwrap();
+    let mut conf = Conf::from(*defaults);
+    conf.with_file(file_name.to_str().unwrap());
+
+    measure("first update", &mut conf);
+    measure("update, nothing changed", &mut conf);
+
+    fs::remove_file(file_name).unwrap();
+}
+
+fn main() {
+    // The defaults array is built on the stack, so give it room
+    thread::Builder::new()
+        .stack_size(256 * 1024 * 1024)
+        .spawn(run)
+        .unwrap()
+        .join()
+        .unwrap();
+}
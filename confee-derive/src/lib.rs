@@ -0,0 +1,88 @@
+//! Derive macro behind confee's `derive` feature. Use it through `confee::conf::Confee`
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Generates, for a struct with named fields, the keys and defaults of its configuration and a constructor
+/// reading it from a Conf. Each field is a key, named after the field unless `#[confee(key = "...")]`
+/// says otherwise, with the default given by `#[confee(default = "...")]`, or empty. Field types are
+/// parsed with FromStr.
+///
+/// The struct gets:
+/// - `KEYS`, the keys in field order
+/// - `DEFAULTS`, the (key, default) pairs in field order
+/// - `conf()`, a Conf holding the defaults
+/// - `from_conf(&Conf)`, which parses every field out of a Conf, as does its TryFrom<&Conf> impl
+#[proc_macro_derive(Confee, attributes(confee))]
+pub fn derive_confee(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(&input, "Confee can only be derived for structs"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(&input, "Confee needs a struct with named fields"));
+    };
+
+    let mut idents = Vec::new();
+    let mut keys = Vec::new();
+    let mut defaults = Vec::new();
+    for field in &fields.named {
+        let ident = field.ident.clone().expect("named fields have an ident");
+        let mut key = ident.to_string();
+        let mut default = String::new();
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("confee")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("default") {
+                    default = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("key") {
+                    key = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `default` or `key`"))
+                }
+            })?;
+        }
+        idents.push(ident);
+        keys.push(key);
+        defaults.push(default);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let count = keys.len();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// The configuration keys, in field order
+            pub const KEYS: [&'static str; #count] = [#(#keys),*];
+            /// The (key, default) pairs, in field order
+            pub const DEFAULTS: [(&'static str, &'static str); #count] = [#((#keys, #defaults)),*];
+
+            /// Creates a Conf holding the defaults
+            pub fn conf() -> ::confee::conf::Conf {
+                ::confee::conf::Conf::from(::confee::conf::embed::to_owned(Self::DEFAULTS))
+            }
+
+            /// Parses every field out of conf
+            pub fn from_conf(conf: &::confee::conf::Conf) -> ::core::result::Result<Self, ::confee::conf::ConfError> {
+                ::core::result::Result::Ok(Self {
+                    #(#idents: conf.try_get(#keys)?,)*
+                })
+            }
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<&::confee::conf::Conf> for #name #ty_generics #where_clause {
+            type Error = ::confee::conf::ConfError;
+
+            fn try_from(conf: &::confee::conf::Conf) -> ::core::result::Result<Self, Self::Error> {
+                Self::from_conf(conf)
+            }
+        }
+    })
+}
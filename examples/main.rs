@@ -1,24 +1,19 @@
 
 use std::env;
+use confee::conf;
 use confee::conf::*;
 use std::net::IpAddr;
 use std::path::PathBuf;
 
-macro_rules! conf_defaults {
-    () => {
-        [
-            ("log".to_string(), "stdout".to_string()),
-            ("dir".to_string(), "/var/www/html/".to_string()),
-            ("addr".to_string(), "127.0.0.1".to_string()),
-            ("port".to_string(), "8080".to_string()),
-        ]
-    };
-}
-
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    let mut conf = Conf::from(conf_defaults!());
+    let mut conf = Conf::from(conf! {
+        log: "stdout",
+        dir: "/var/www/html/",
+        addr: "127.0.0.1",
+        port: 8080,
+    });
     match conf.with_file(&args[1]).update() {
         Ok(_) => println!("Successfully updated configuration!"),
         Err(e) => panic!("Error updating configuration: {}", e),
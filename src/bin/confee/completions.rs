@@ -0,0 +1,34 @@
+use crate::schema::Schema;
+use crate::Cli;
+use clap::builder::PossibleValuesParser;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use std::io;
+use std::process::ExitCode;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Shell to generate completions for
+    shell: Shell,
+    /// JSON schema whose keys are offered when completing `get` and `set`
+    #[arg(long)]
+    schema: Option<String>,
+}
+
+/// Prints the completion script for args.shell, e.g. `confee completions bash > /etc/bash_completion.d/confee`
+pub fn run(args: Args) -> Result<ExitCode, String> {
+    let mut command = Cli::command();
+    if let Some(schema) = args.schema.as_deref().map(Schema::load).transpose()? {
+        let keys: Vec<String> = schema.keys().map(|(k, _)| k.clone()).collect();
+        for name in ["get", "set"] {
+            let keys = keys.clone();
+            command = command.mut_subcommand(name, |sub| {
+                sub.mut_arg("key", |arg| {
+                    arg.value_parser(PossibleValuesParser::new(keys))
+                })
+            });
+        }
+    }
+    clap_complete::generate(args.shell, &mut command, "confee", &mut io::stdout());
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,39 @@
+use confee::conf::{Conf, Format};
+use std::fs;
+use std::process::ExitCode;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// File to convert
+    file: String,
+    /// Format to convert to: confee, dotenv, ini, toml, json or yaml
+    #[arg(long)]
+    to: Format,
+    /// Format of the input, guessed from its extension when omitted
+    #[arg(long)]
+    from: Option<Format>,
+    /// Delimiter between keys and values in confee files
+    #[arg(long, default_value_t = ':')]
+    delim: char,
+    /// Write the result to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+pub fn run(args: Args) -> Result<ExitCode, String> {
+    let from = args
+        .from
+        .or_else(|| Format::from_extension(&args.file))
+        .unwrap_or(Format::Confee);
+    let contents = fs::read_to_string(&args.file).map_err(|e| format!("{}: {}", args.file, e))?;
+    let pairs = from
+        .parse(&contents, args.delim)
+        .map_err(|e| format!("{}: {}", args.file, e))?;
+    let mut conf: Conf = pairs.into_iter().collect();
+    let converted = conf.with_delim(args.delim).export(args.to);
+    match &args.output {
+        Some(output) => fs::write(output, converted).map_err(|e| format!("{}: {}", output, e))?,
+        None => print!("{}", converted),
+    }
+    Ok(ExitCode::SUCCESS)
+}
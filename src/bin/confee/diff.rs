@@ -0,0 +1,89 @@
+use crate::schema::Schema;
+use crate::{load_defaults, load_pairs};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Configuration file to compare from
+    old: String,
+    /// Configuration file to compare to
+    new: String,
+    /// confee file holding the default value of every known key, applied to both files
+    #[arg(long)]
+    defaults: Option<String>,
+    /// JSON schema whose defaults are applied to both files
+    #[arg(long)]
+    schema: Option<String>,
+    /// Delimiter between keys and values
+    #[arg(long, default_value_t = ':')]
+    delim: char,
+    /// Print the differences as a JSON object
+    #[arg(long)]
+    json: bool,
+}
+
+/// Exits with a non-zero code when the files differ, like diff(1)
+pub fn run(args: Args) -> Result<ExitCode, String> {
+    let schema = args.schema.as_deref().map(Schema::load).transpose()?;
+    let defaults = load_defaults(args.defaults.as_deref(), schema.as_ref(), args.delim)?;
+    let old = effective(&args.old, defaults.as_deref(), args.delim)?;
+    let new = effective(&args.new, defaults.as_deref(), args.delim)?;
+
+    let added: BTreeMap<_, _> = new.iter().filter(|(k, _)| !old.contains_key(*k)).collect();
+    let removed: BTreeMap<_, _> = old.iter().filter(|(k, _)| !new.contains_key(*k)).collect();
+    let changed: BTreeMap<_, _> = old
+        .iter()
+        .filter_map(|(k, o)| new.get(k).filter(|n| *n != o).map(|n| (k, (o, n))))
+        .collect();
+
+    if args.json {
+        let changed: BTreeMap<_, _> = changed
+            .iter()
+            .map(|(k, (o, n))| (k, json!({ "old": o, "new": n })))
+            .collect();
+        let diff = json!({ "added": added, "removed": removed, "changed": changed });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&diff).map_err(|e| e.to_string())?
+        );
+    } else {
+        for (key, value) in &added {
+            println!("+ {}{} {}", key, args.delim, value);
+        }
+        for (key, value) in &removed {
+            println!("- {}{} {}", key, args.delim, value);
+        }
+        for (key, (old, new)) in &changed {
+            println!("~ {}{} {} -> {}", key, args.delim, old, new);
+        }
+    }
+    Ok(
+        if added.is_empty() && removed.is_empty() && changed.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        },
+    )
+}
+
+/// Applies file_name over defaults the way Conf::update does, ignoring keys that have no default. Without
+/// defaults, the file is taken as it is
+fn effective(
+    file_name: &str,
+    defaults: Option<&[(String, String)]>,
+    delim: char,
+) -> Result<BTreeMap<String, String>, String> {
+    let pairs = load_pairs(file_name, delim)?;
+    let Some(defaults) = defaults else {
+        return Ok(pairs.into_iter().collect());
+    };
+    let mut effective: BTreeMap<String, String> = defaults.iter().cloned().collect();
+    for (key, value) in pairs {
+        if let Some(v) = effective.get_mut(&key) {
+            *v = value;
+        }
+    }
+    Ok(effective)
+}
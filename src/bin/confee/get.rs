@@ -0,0 +1,39 @@
+use confee::conf::Format;
+use std::fs;
+use std::process::ExitCode;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// File to read from
+    file: String,
+    /// Key to print the value of
+    key: String,
+    /// Format of the file, guessed from its extension when omitted
+    #[arg(long)]
+    format: Option<Format>,
+    /// Delimiter between keys and values in confee files
+    #[arg(long, default_value_t = ':')]
+    delim: char,
+}
+
+/// Prints the value key takes in the file, exiting with a non-zero code when the file doesn't set it
+pub fn run(args: Args) -> Result<ExitCode, String> {
+    let format = args
+        .format
+        .or_else(|| Format::from_extension(&args.file))
+        .unwrap_or(Format::Confee);
+    let contents = fs::read_to_string(&args.file).map_err(|e| format!("{}: {}", args.file, e))?;
+    let pairs = format
+        .parse(&contents, args.delim)
+        .map_err(|e| format!("{}: {}", args.file, e))?;
+    match pairs.into_iter().rev().find(|(k, _)| *k == args.key) {
+        Some((_, value)) => {
+            println!("{}", value);
+            Ok(ExitCode::SUCCESS)
+        }
+        None => {
+            eprintln!("{}: no such key: {}", args.file, args.key);
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use crate::load_defaults;
+use crate::schema::Schema;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::ExitCode;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// JSON schema describing each key
+    #[arg(long, required_unless_present = "defaults")]
+    schema: Option<String>,
+    /// confee file holding the default value of every key
+    #[arg(long)]
+    defaults: Option<String>,
+    /// Write the sample to this file instead of stdout
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Overwrite the output file if it already exists
+    #[arg(long)]
+    force: bool,
+    /// Delimiter between keys and values
+    #[arg(long, default_value_t = ':')]
+    delim: char,
+}
+
+pub fn run(args: Args) -> Result<ExitCode, String> {
+    let schema = args.schema.as_deref().map(Schema::load).transpose()?;
+    let defaults =
+        load_defaults(args.defaults.as_deref(), schema.as_ref(), args.delim)?.unwrap_or_default();
+
+    let mut sample = String::from("# Sample configuration generated by confee init\n");
+    for (key, value) in &defaults {
+        sample.push('\n');
+        if let Some(key_schema) = schema.as_ref().and_then(|s| s.get(key)) {
+            if let Some(description) = &key_schema.description {
+                for line in description.lines() {
+                    sample.push_str(&format!("# {}\n", line));
+                }
+            }
+            let required = if key_schema.required {
+                ", required"
+            } else {
+                ""
+            };
+            sample.push_str(&format!("# type: {}{}\n", key_schema.kind.name(), required));
+        }
+        sample.push_str(&format!("{}{} {}\n", key, args.delim, value));
+    }
+
+    match &args.output {
+        Some(output) => {
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .create_new(!args.force)
+                .open(output)
+                .map_err(|e| format!("{}: {}", output, e))?;
+            file.write_all(sample.as_bytes())
+                .map_err(|e| format!("{}: {}", output, e))?;
+        }
+        None => print!("{}", sample),
+    }
+    Ok(ExitCode::SUCCESS)
+}
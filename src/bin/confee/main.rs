@@ -0,0 +1,90 @@
+//! confee's command line tool, for linting and scripting confee configuration files from CI pipelines and
+//! shell scripts
+
+mod completions;
+mod convert;
+mod diff;
+mod get;
+mod init;
+mod schema;
+mod set;
+mod validate;
+
+use clap::{Parser, Subcommand};
+use confee::conf::parse_pairs;
+use schema::Schema;
+use std::fs;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "confee",
+    version,
+    about = "Work with confee configuration files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check a configuration file, exiting with a non-zero code on any problem
+    Validate(validate::Args),
+    /// Convert a configuration file between the confee, dotenv, INI, TOML and JSON formats
+    Convert(convert::Args),
+    /// Show the keys added, removed or changed between two configuration files
+    Diff(diff::Args),
+    /// Write a commented sample configuration file, built from a schema or a defaults file
+    Init(init::Args),
+    /// Print the value of a single key
+    Get(get::Args),
+    /// Set the value of a single key in place, keeping the rest of the file as it is
+    Set(set::Args),
+    /// Print a shell completion script, optionally completing key names from a schema
+    Completions(completions::Args),
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Validate(args) => validate::run(args),
+        Command::Convert(args) => convert::run(args),
+        Command::Diff(args) => diff::run(args),
+        Command::Get(args) => get::run(args),
+        Command::Init(args) => init::run(args),
+        Command::Set(args) => set::run(args),
+        Command::Completions(args) => completions::run(args),
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("confee: {}", e);
+        ExitCode::from(2)
+    })
+}
+
+/// Gets the defaults from the defaults file when there is one, or else from the schema
+fn load_defaults(
+    defaults: Option<&str>,
+    schema: Option<&Schema>,
+    delim: char,
+) -> Result<Option<Vec<(String, String)>>, String> {
+    Ok(match (defaults, schema) {
+        (Some(defaults), _) => Some(load_pairs(defaults, delim)?),
+        (None, Some(schema)) => Some(
+            schema
+                .keys()
+                .map(|(k, s)| (k.clone(), s.default.clone().unwrap_or_default()))
+                .collect(),
+        ),
+        (None, None) => None,
+    })
+}
+
+/// Reads every (key, value) pair of a confee file, in order
+fn load_pairs(file_name: &str, delim: char) -> Result<Vec<(String, String)>, String> {
+    let contents = fs::read_to_string(file_name).map_err(|e| format!("{}: {}", file_name, e))?;
+    let pairs = parse_pairs(&contents, delim).map_err(|e| format!("{}: {}", file_name, e))?;
+    Ok(pairs
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect())
+}
@@ -0,0 +1,107 @@
+use confee::conf::Conf;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+
+/// Describes the keys an application understands, as a JSON object mapping each key to its KeySchema:
+///
+/// ```json
+/// { "port": { "type": "port", "required": true, "default": "8080", "description": "TCP port" } }
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct Schema(BTreeMap<String, KeySchema>);
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeySchema {
+    #[serde(rename = "type", default)]
+    pub kind: Kind,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Types a value can be checked against
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    #[default]
+    String,
+    Bool,
+    Integer,
+    Unsigned,
+    Float,
+    Port,
+    Ip,
+}
+
+impl Kind {
+    fn accepts(self, value: &str) -> bool {
+        match self {
+            Kind::String => true,
+            Kind::Bool => value.parse::<bool>().is_ok(),
+            Kind::Integer => value.parse::<i64>().is_ok(),
+            Kind::Unsigned => value.parse::<u64>().is_ok(),
+            Kind::Float => value.parse::<f64>().is_ok(),
+            Kind::Port => value.parse::<u16>().is_ok(),
+            Kind::Ip => value.parse::<IpAddr>().is_ok(),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::String => "string",
+            Kind::Bool => "bool",
+            Kind::Integer => "integer",
+            Kind::Unsigned => "unsigned",
+            Kind::Float => "float",
+            Kind::Port => "port",
+            Kind::Ip => "ip",
+        }
+    }
+}
+
+impl Schema {
+    pub fn load(file_name: &str) -> Result<Self, String> {
+        let contents =
+            fs::read_to_string(file_name).map_err(|e| format!("{}: {}", file_name, e))?;
+        serde_json::from_str(&contents).map_err(|e| format!("{}: {}", file_name, e))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = (&String, &KeySchema)> {
+        self.0.iter()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&KeySchema> {
+        self.0.get(key)
+    }
+
+    /// Checks the effective values in conf, returning a message per problem found
+    pub fn check(&self, conf: &Conf) -> Vec<String> {
+        let mut problems = Vec::new();
+        for (key, schema) in self.keys() {
+            let value = &conf[key.as_str()];
+            if value.is_empty() {
+                if schema.required {
+                    problems.push(format!("missing required key: {}", key));
+                }
+            } else if !schema.kind.accepts(value) {
+                problems.push(format!(
+                    "{}: expected {}, found \"{}\"",
+                    key,
+                    schema.kind.name(),
+                    value
+                ));
+            }
+        }
+        problems
+    }
+}
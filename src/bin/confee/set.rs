@@ -0,0 +1,26 @@
+use confee::conf::Editor;
+use std::process::ExitCode;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// confee file to edit in place
+    file: String,
+    /// Key to set
+    key: String,
+    /// New value
+    value: String,
+    /// Delimiter between keys and values
+    #[arg(long, default_value_t = ':')]
+    delim: char,
+}
+
+/// Sets the value of key, leaving every other line of the file untouched
+pub fn run(args: Args) -> Result<ExitCode, String> {
+    let mut editor =
+        Editor::open(&args.file, args.delim).map_err(|e| format!("{}: {}", args.file, e))?;
+    editor
+        .set(&args.key, &args.value)
+        .save()
+        .map_err(|e| format!("{}: {}", args.file, e))?;
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,97 @@
+use crate::schema::Schema;
+use crate::{load_defaults, load_pairs};
+use confee::conf::Conf;
+use std::process::ExitCode;
+
+#[derive(clap::Args)]
+pub struct Args {
+    /// Configuration file to check
+    file: String,
+    /// confee file holding the default value of every known key
+    #[arg(long)]
+    defaults: Option<String>,
+    /// JSON schema describing the type and presence of each key
+    #[arg(long)]
+    schema: Option<String>,
+    /// Delimiter between keys and values
+    #[arg(long, default_value_t = ':')]
+    delim: char,
+    /// Print the outcome as a JSON object
+    #[arg(long)]
+    json: bool,
+}
+
+/// Outcome of validating a file
+#[derive(serde::Serialize)]
+struct Report<'a> {
+    file: &'a str,
+    valid: bool,
+    problems: &'a [String],
+}
+
+pub fn run(args: Args) -> Result<ExitCode, String> {
+    let schema = args.schema.as_deref().map(Schema::load).transpose()?;
+    let defaults = load_defaults(args.defaults.as_deref(), schema.as_ref(), args.delim)?;
+
+    let mut problems = Vec::new();
+    let pairs = match load_pairs(&args.file, args.delim) {
+        Ok(pairs) => pairs,
+        Err(e) if !args.json => {
+            eprintln!("{}", e);
+            return Ok(ExitCode::FAILURE);
+        }
+        Err(e) => {
+            problems.push(e);
+            return report(&args, &problems);
+        }
+    };
+    // Without defaults or a schema every key is known, and only the syntax gets checked
+    let defaults = match defaults {
+        Some(defaults) => {
+            for (key, _) in &pairs {
+                let known = defaults.iter().any(|(k, _)| k == key)
+                    || schema.as_ref().is_some_and(|s| s.contains(key));
+                if !known {
+                    problems.push(format!("unknown key: {}", key));
+                }
+            }
+            defaults
+        }
+        None => pairs.into_iter().map(|(k, _)| (k, String::new())).collect(),
+    };
+
+    let mut conf: Conf = defaults.into_iter().collect();
+    match conf.with_file(&args.file).with_delim(args.delim).update() {
+        Ok(_) => {
+            if let Some(schema) = &schema {
+                problems.extend(schema.check(&conf));
+            }
+        }
+        Err(e) => problems.push(e.to_string()),
+    }
+
+    report(&args, &problems)
+}
+
+fn report(args: &Args, problems: &[String]) -> Result<ExitCode, String> {
+    if args.json {
+        let report = Report {
+            file: &args.file,
+            valid: problems.is_empty(),
+            problems,
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?
+        );
+    } else {
+        for problem in problems {
+            eprintln!("{}: {}", args.file, problem);
+        }
+    }
+    Ok(if problems.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
@@ -1,22 +1,250 @@
-use std::fmt::{self, Display, Formatter};
-use std::ops::Index;
-use std::str::FromStr;
-use std::{collections::HashMap, fs};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::ops::Index;
+use core::str::FromStr;
+use core::panic::Location;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use std::process::Command;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+use std::fs::File;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+use std::io::{BufRead, BufReader, Read};
+
+mod alias;
+mod audit;
+mod borrowed;
+mod builder;
+#[cfg(feature = "serde")]
+mod de;
+mod delim;
+mod diff;
+mod duplicates;
+#[cfg(feature = "std")]
+mod edit;
+#[doc(hidden)]
+pub mod embed;
+mod error;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+mod expiry;
+mod format;
+mod history;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+mod include;
+mod intern;
+mod interpolate;
+mod iter;
+mod limits;
+mod lines;
+#[cfg(feature = "std")]
+mod merge;
+mod metrics;
+mod patch;
+#[cfg(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+mod preload;
+mod provenance;
+mod quote;
+mod rollback;
+mod rule;
+#[cfg(feature = "serde")]
+mod ser;
+#[cfg(feature = "std")]
+mod shared;
+#[cfg(all(unix, feature = "sighup"))]
+mod sighup;
+#[cfg(feature = "std")]
+mod miss;
+#[cfg(feature = "interactive")]
+mod prompt;
+mod source;
+mod store;
+mod subscribe;
+#[cfg(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+mod unblock;
+mod units;
+#[cfg(all(feature = "watch", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+mod watch;
+#[cfg(feature = "web")]
+mod web;
+pub use audit::AccessEvent;
+pub use borrowed::ConfRef;
+pub use builder::ConfBuilder;
+pub use diff::{ChangeSet, KeyChange};
+pub use duplicates::DuplicatePolicy;
+#[cfg(feature = "std")]
+pub use edit::Editor;
+pub use error::ConfError;
+pub use format::Format;
+pub use history::Change;
+pub use iter::{Iter, Keys, Values};
+pub use limits::Limits;
+#[cfg(feature = "std")]
+pub use merge::{Conflict, Merge3};
+pub use metrics::ConfMetrics;
+pub use patch::Patch;
+pub use provenance::Provenance;
+#[cfg(all(feature = "watch", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+pub use watch::Watcher;
+#[cfg(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+pub use watch::AsyncWatcher;
+pub use rule::{Rule, Validator};
+#[cfg(feature = "std")]
+pub use shared::SharedConf;
+#[cfg(feature = "std")]
+pub use miss::MissedKey;
+pub use source::Source;
+/// Derives the keys, defaults and a typed constructor of a configuration from a struct. Requires the
+/// `derive` feature
+///
+/// # Examples
+///
+/// ```
+/// use confee::conf::Confee;
+/// # use std::net::IpAddr;
+///
+/// #[derive(Confee)]
+/// struct AppConf {
+///     #[confee(default = "8080")]
+///     port: u16,
+///     #[confee(key = "addr", default = "127.0.0.1")]
+///     address: IpAddr,
+///     #[confee(default = "stdout")]
+///     log: String,
+/// }
+///
+/// assert_eq!(AppConf::KEYS, ["port", "addr", "log"]);
+/// let mut conf = AppConf::conf();
+/// conf.update_from_str("port: 9090").unwrap();
+/// let app = AppConf::from_conf(&conf).unwrap();
+/// assert_eq!(app.port, 9090);
+/// assert_eq!(app.log, "stdout");
+/// ```
+#[cfg(feature = "derive")]
+pub use confee_derive::Confee;
+
+#[cfg(feature = "encryption")]
+mod crypto;
+#[cfg(feature = "encryption")]
+pub use crypto::encrypt;
+#[cfg(feature = "signing")]
+mod signature;
+#[cfg(feature = "signing")]
+pub use signature::sign;
+
+#[cfg(feature = "zeroize")]
+use zeroize::{Zeroize, Zeroizing};
+
+/// Map backing the (key, value) pairs. The `ahash` feature swaps SipHash for aHash, which is noticeably
+/// faster for configurations with tens of thousands of keys
+#[cfg(feature = "ahash")]
+type PairMap<K, V> = HashMap<K, V, ahash::RandomState>;
+#[cfg(all(feature = "std", not(feature = "ahash")))]
+type PairMap<K, V> = HashMap<K, V>;
+
+/// Set of keys, such as the secret ones. Without the `std` feature a BTreeSet stands in for the HashSet
+#[cfg(feature = "std")]
+type KeySet = HashSet<String>;
+#[cfg(not(feature = "std"))]
+type KeySet = alloc::collections::BTreeSet<String>;
 
 const DEFAULT_DELIM: char = ':';
+#[cfg(feature = "std")]
+const PROFILE_ENV_VAR: &str = "CONFEE_PROFILE";
+const DEFAULT_COMMENT_CHARS: &[char] = &['#'];
+const REDACTED: &str = "****";
+/// Keys under this prefix are secret without being marked
+const SECRET_PREFIX: &str = "secret.";
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
 
 /// Conf is more or less a wrapper around HashMap<String, String>, and it controls access to (key, value) pairs, which
 /// represent configuration properties for an application and their respective values. It offers methods 
 /// to ergonomically and safely parse a configuration file and update the defaults previously set by the user.
 /// 
-
-#[derive(Debug)]
 pub struct Conf {
-    pairs: HashMap<String, String>,
-    delim: Option<char>,
+    pairs: store::Store<String, Arc<String>>,
+    defaults: store::Store<String, Arc<String>>,
+    delims: delim::Delims,
+    comment_chars: Vec<char>,
+    inline_comments: bool,
+    quotes: bool,
+    continuations: bool,
+    list_separator: char,
+    strict: bool,
+    interpolation: bool,
+    templates: BTreeMap<String, String>,
+    old_values: BTreeMap<String, String>,
+    // What the running update changed, for putting it back should the update fail
+    rollback: rollback::Rollback,
+    sections: bool,
+    section: String,
+    case_insensitive: bool,
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    includes: bool,
+    // The files being included, innermost last, for telling include cycles
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    including: Vec<std::path::PathBuf>,
+    duplicates: Option<DuplicatePolicy>,
+    // Keys set by the file being read, when duplicates among them matter
+    seen: KeySet,
+    profile: Option<String>,
+    // Keys the file being read set for the active profile, which its other values for them don't override
+    profiled: KeySet,
+    format: Option<Format>,
     conf_file_name: String,
+    #[cfg(feature = "std")]
+    candidates: Vec<String>,
+    #[cfg(feature = "std")]
+    layer_candidates: bool,
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    conf_dir: Option<String>,
+    #[cfg(feature = "std")]
+    loaded_files: Vec<String>,
+    #[cfg(feature = "std")]
+    env_prefix: Option<String>,
+    #[cfg(feature = "std")]
+    args: Vec<String>,
     updated: bool,
     empty_string: String,
+    secrets: KeySet,
+    audited: KeySet,
+    locked: KeySet,
+    required: KeySet,
+    found: KeySet,
+    descriptions: BTreeMap<String, String>,
+    comments: BTreeMap<String, String>,
+    pending_comment: String,
+    interner: Option<intern::Interner>,
+    history: Option<history::History>,
+    origin: Arc<provenance::Origin>,
+    origins: BTreeMap<String, Arc<provenance::Origin>>,
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    snapshot: Option<String>,
+    #[cfg(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    preloaded: BTreeMap<String, String>,
+    limits: Option<Limits>,
+    validators: rule::Validators,
+    subscribers: subscribe::Subscribers,
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    expiry: expiry::Expiry,
+    audit_hook: Option<audit::AuditHook>,
+    aliases: BTreeMap<String, String>,
+    deprecation_hook: Option<alias::DeprecationHook>,
+    metrics: Option<metrics::Metrics>,
+    #[cfg(feature = "std")]
+    misses: miss::Misses,
+    source: Option<source::BoxedSource>,
+    #[cfg(feature = "encryption")]
+    decryption_key: Option<crypto::DecryptionKey>,
+    #[cfg(feature = "signing")]
+    public_key: Option<[u8; 32]>,
+    #[cfg(feature = "mmap")]
+    mmap: bool,
 }
 
 impl Conf {
@@ -25,144 +253,4012 @@ impl Conf {
     /// # Examples
     ///
     /// ```
+    /// # use confee::conf::Conf;
     /// let mut conf = Conf::from([
     ///     ("foo".to_string(), "bar".to_string()),
     ///     ("yee".to_string(), "haw".to_string()),
     /// ]);
     /// ```
     pub fn from<const N: usize>(defaults: [(String, String); N]) -> Self {
-        Self {
-            pairs: HashMap::from(defaults),
-            delim: None,
-            conf_file_name: "".to_string(),
-            empty_string: "".to_string(),
-            updated: false,
-        }
+        Self::from_iter(defaults)
+    }
+    /// Creates a Conf from defaults given as anything iterable, with keys and values of any type that
+    /// converts into String, such as `&str` literals. Conf owns its values, so each is copied once; see
+    /// ConfRef for borrowing them instead
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from_defaults([("port", "8080"), ("log", "stdout")]);
+    /// assert_eq!(conf["port"], "8080");
+    ///
+    /// let keys = vec!["host", "user"];
+    /// let conf = Conf::from_defaults(keys.iter().map(|key| (*key, "")));
+    /// assert!(conf.contains_key("user"));
+    /// ```
+    pub fn from_defaults<I, K, V>(defaults: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        defaults.into_iter().map(|(key, value)| (key.into(), value.into())).collect()
+    }
+    /// Starts a ConfBuilder with the given defaults, for setting up and loading a Conf in one expression
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::builder([("port", "8080")]).with_str("port: 9090").load().unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    /// ```
+    pub fn builder<I, K, V>(defaults: I) -> ConfBuilder
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        ConfBuilder::new(defaults)
     }
 
     /// Sets the delimiter for this Conf
     pub fn with_delim(&mut self, delim: char) -> &mut Self {
-        self.delim = Some(delim);
+        self.delims = delim.into();
         self
     }
     pub fn and_delim(&mut self, delim: char) -> &mut Self {
         self.with_delim(delim)
     }
-    /// Gets the delimiter set for this Conf
+    /// Sets several delimiters this Conf accepts between keys and values, e.g. both `:` and `=`. Each line
+    /// is split at the first one it holds, and the first one given is the one written
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from_defaults([("port", "8080"), ("log", "stdout")]);
+    /// conf.with_delims(&[':', '=']).update_from_str("port = 9090\nlog: stderr").unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    /// assert_eq!(conf.to_string(), "log: stderr\nport: 9090\n");
+    /// ```
+    pub fn with_delims(&mut self, delims: &[char]) -> &mut Self {
+        self.delims = delim::Delims::new(delims.iter().map(char::to_string));
+        self
+    }
+    pub fn and_delims(&mut self, delims: &[char]) -> &mut Self {
+        self.with_delims(delims)
+    }
+    /// Sets a delimiter of several characters for this Conf, such as `=>`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from_defaults([("url", "")]);
+    /// conf.with_delim_str("=>").update_from_str("url => http://localhost:8080").unwrap();
+    /// assert_eq!(conf["url"], "http://localhost:8080");
+    /// ```
+    pub fn with_delim_str(&mut self, delim: &str) -> &mut Self {
+        self.delims = delim::Delims::new([delim.to_string()]);
+        self
+    }
+    pub fn and_delim_str(&mut self, delim: &str) -> &mut Self {
+        self.with_delim_str(delim)
+    }
+    /// Gets the delimiter set for this Conf. Of several, this is the first, and of a string delimiter its
+    /// first character; see delim_str
     pub fn delim(&self) -> char {
-        self.delim.unwrap_or(DEFAULT_DELIM)
+        self.delim_str().chars().next().unwrap_or(DEFAULT_DELIM)
+    }
+    /// Gets the delimiter this Conf writes between keys and values
+    pub fn delim_str(&self) -> &str {
+        self.delims.written()
+    }
+
+    /// Sets the characters that start a comment line, `#` by default
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_comment_chars(&['#', ';'])
+    ///     .with_inline_comments(true)
+    ///     .update_from_str("; the port\nport: 9090 # was 8080")
+    ///     .unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    /// ```
+    pub fn with_comment_chars(&mut self, chars: &[char]) -> &mut Self {
+        self.comment_chars = chars.to_vec();
+        self
+    }
+    pub fn and_comment_chars(&mut self, chars: &[char]) -> &mut Self {
+        self.with_comment_chars(chars)
+    }
+    /// Makes update strip trailing comments from values: a comment character preceded by whitespace, and
+    /// everything after it. Off by default, as values may legitimately hold e.g. ` #`
+    pub fn with_inline_comments(&mut self, inline_comments: bool) -> &mut Self {
+        self.inline_comments = inline_comments;
+        self
+    }
+    pub fn and_inline_comments(&mut self, inline_comments: bool) -> &mut Self {
+        self.with_inline_comments(inline_comments)
+    }
+    /// Makes update read values wrapped in double quotes as written between them, so they can hold
+    /// whitespace around them and, through the escapes `\n`, `\r`, `\t`, `\0`, `\\` and `\"`, any other
+    /// character. save quotes the values that need it in turn. Off by default, keeping quotes as written
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, ConfError};
+    /// let mut conf = Conf::from_defaults([("prompt", ""), ("motd", "")]);
+    /// conf.with_quotes(true).update_from_str("prompt: \"> \"\nmotd: \"Welcome!\\nBe nice\"").unwrap();
+    /// assert_eq!(conf["prompt"], "> ");
+    /// assert_eq!(conf["motd"], "Welcome!\nBe nice");
+    /// let unterminated = conf.update_from_str("motd: \"Welcome!");
+    /// assert!(matches!(unterminated, Err(ConfError::Parse { line_no: Some(1), .. })));
+    /// ```
+    pub fn with_quotes(&mut self, quotes: bool) -> &mut Self {
+        self.quotes = quotes;
+        self
+    }
+    pub fn and_quotes(&mut self, quotes: bool) -> &mut Self {
+        self.with_quotes(quotes)
+    }
+    /// Makes update read values spanning several lines. A pair ending in `\` goes on at the start of the
+    /// next line, and indented lines below a pair add lines to its value. Off by default, as values may
+    /// end in `\` and pairs may be indented
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from_defaults([("motd", ""), ("greeting", "")]);
+    /// conf.with_continuations(true)
+    ///     .update_from_str("motd: Welcome!\n  Be nice\ngreeting: Hello, \\\n    world")
+    ///     .unwrap();
+    /// assert_eq!(conf["motd"], "Welcome!\nBe nice");
+    /// assert_eq!(conf["greeting"], "Hello, world");
+    /// ```
+    pub fn with_continuations(&mut self, continuations: bool) -> &mut Self {
+        self.continuations = continuations;
+        self
+    }
+    pub fn and_continuations(&mut self, continuations: bool) -> &mut Self {
+        self.with_continuations(continuations)
+    }
+    /// Makes update read `include: path` lines, outside any section, as the contents of the file at path,
+    /// so a large configuration can be split in several files. Relative paths are resolved against the file
+    /// holding the line, or else the current directory. A path whose file name holds `*` or `?` wildcards,
+    /// such as `conf.d/*.conf`, includes every file it matches, in name order. Included files may include
+    /// others, but not themselves, directly or not. Off by default, as `include` may be a key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// # let dir = std::env::temp_dir().join(format!("confee-include-{}", std::process::id()));
+    /// # std::fs::create_dir_all(dir.join("conf.d")).unwrap();
+    /// # let path = dir.join("app.conf");
+    /// # let path = path.to_str().unwrap();
+    /// std::fs::write(path, "port: 9090\ninclude: conf.d/*.conf\n").unwrap();
+    /// # std::fs::write(dir.join("conf.d/log.conf"), "log: stderr\n").unwrap();
+    /// let mut conf = Conf::from_defaults([("port", "8080"), ("log", "stdout")]);
+    /// conf.with_file(path).with_includes(true).update()?;
+    /// assert_eq!(conf["log"], "stderr");
+    /// std::fs::write(dir.join("conf.d/loop.conf"), "include: loop.conf\n").unwrap();
+    /// let cycle = conf.update().unwrap_err();
+    /// assert!(matches!(cycle, confee::conf::ConfError::Invalid { line_no: Some(1), .. }));
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// # Ok::<(), confee::conf::ConfError>(())
+    /// ```
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn with_includes(&mut self, includes: bool) -> &mut Self {
+        self.includes = includes;
+        self
+    }
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn and_includes(&mut self, includes: bool) -> &mut Self {
+        self.with_includes(includes)
+    }
+    /// Sets the character separating the items of list values, read by get_vec. Defaults to `,`
+    pub fn with_list_separator(&mut self, separator: char) -> &mut Self {
+        self.list_separator = separator;
+        self
+    }
+    pub fn and_list_separator(&mut self, separator: char) -> &mut Self {
+        self.with_list_separator(separator)
+    }
+
+    /// Makes update expand `${name}` references in values to the value of key `name` or, when Conf holds no
+    /// such key, of environment variable `name`. References may point at values holding references
+    /// themselves, though not in a cycle, and `$${` stands for a literal `${`. Values are expanded once the
+    /// whole update is read, so they may refer to keys set further down, and again on every later update.
+    /// Defaults may hold references too
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([
+    ///     ("dir".to_string(), "/var/log".to_string()),
+    ///     ("log_file".to_string(), "${dir}/app.log".to_string()),
+    ///     ("motd".to_string(), "".to_string()),
+    /// ]);
+    /// conf.with_interpolation(true).update_from_str("dir: /tmp\nmotd: use $${dir}").unwrap();
+    /// assert_eq!(conf["log_file"], "/tmp/app.log");
+    /// assert_eq!(conf["motd"], "use ${dir}");
+    /// assert!(conf.update_from_str("dir: ${log_file}").is_err());
+    /// conf.update_from_str("dir: /srv").unwrap();
+    /// assert_eq!(conf["log_file"], "/srv/app.log");
+    /// ```
+    pub fn with_interpolation(&mut self, interpolation: bool) -> &mut Self {
+        self.interpolation = interpolation;
+        self.templates.clear();
+        if interpolation {
+            for (key, default) in self.defaults.iter() {
+                if default.contains("${") && self.pairs.get(key).is_some_and(|v| v == default) {
+                    self.templates.insert(key.clone(), default.to_string());
+                }
+            }
+        }
+        self
+    }
+    pub fn and_interpolation(&mut self, interpolation: bool) -> &mut Self {
+        self.with_interpolation(interpolation)
+    }
+
+    /// Makes update fail on keys Conf doesn't hold, rather than ignore them, so typos such as `prot: 8080`
+    /// don't go unnoticed. Applies to patches and overrides too
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, ConfError};
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// let result = conf.with_strict(true).update_from_str("prot: 9090");
+    /// assert!(matches!(result, Err(ConfError::UnknownKey { line_no: Some(1), .. })));
+    /// ```
+    pub fn with_strict(&mut self, strict: bool) -> &mut Self {
+        self.strict = strict;
+        self
+    }
+    pub fn and_strict(&mut self, strict: bool) -> &mut Self {
+        self.with_strict(strict)
+    }
+    /// Sets what update does with a key a file sets more than once. Unless set, the last value wins, but
+    /// strict mode fails the update and validate reports the key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, ConfError, DuplicatePolicy};
+    /// let mut conf = Conf::from_defaults([("port", "8080")]);
+    /// conf.with_duplicates(DuplicatePolicy::FirstWins).update_from_str("port: 9090\nport: 9091").unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    ///
+    /// let result = conf.with_duplicates(DuplicatePolicy::Error).update_from_str("port: 1\nport: 2");
+    /// assert!(matches!(result, Err(ConfError::DuplicateKey { line_no: Some(2), .. })));
+    /// ```
+    pub fn with_duplicates(&mut self, policy: DuplicatePolicy) -> &mut Self {
+        self.duplicates = Some(policy);
+        self
+    }
+    pub fn and_duplicates(&mut self, policy: DuplicatePolicy) -> &mut Self {
+        self.with_duplicates(policy)
+    }
+    /// Makes keys match whatever the case of their ASCII letters, in files, environment variables, get, set
+    /// and the like, so `Port` and `PORT` both set `port`. Keys keep the case of the defaults in output
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from_defaults([("port", "8080")]);
+    /// conf.with_case_insensitive(true).update_from_str("PORT: 9090").unwrap();
+    /// assert_eq!(conf["Port"], "9090");
+    /// assert_eq!(conf.to_string(), "port: 9090\n");
+    /// ```
+    pub fn with_case_insensitive(&mut self, case_insensitive: bool) -> &mut Self {
+        self.case_insensitive = case_insensitive;
+        // Keys marked before are marked as Conf holds them, so that a secret stays masked whatever its case
+        let rekey = |conf: &Self, keys: &KeySet| {
+            keys.iter().map(|key| conf.canonical(key).to_string()).collect()
+        };
+        self.secrets = rekey(self, &self.secrets);
+        self.locked = rekey(self, &self.locked);
+        self.required = rekey(self, &self.required);
+        self.audited = rekey(self, &self.audited);
+        self
+    }
+    pub fn and_case_insensitive(&mut self, case_insensitive: bool) -> &mut Self {
+        self.with_case_insensitive(case_insensitive)
+    }
+
+    /// Makes update recognize INI-style `[section]` headers. Keys below a header are read as nested under
+    /// it, so `port` under `[server]` sets `server.port`. An empty header, `[]`, goes back to the top level
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("server.port".to_string(), "8080".to_string())]);
+    /// conf.with_sections(true).update_from_str("[server]\nport: 9090\n").unwrap();
+    /// assert_eq!(conf.get_in::<u16>("server", "port"), Some(9090));
+    /// ```
+    pub fn with_sections(&mut self, sections: bool) -> &mut Self {
+        self.sections = sections;
+        self
+    }
+    pub fn and_sections(&mut self, sections: bool) -> &mut Self {
+        self.with_sections(sections)
+    }
+
+    /// Sets the format update reads the configuration in. Nested tables and arrays are flattened into dotted
+    /// and indexed keys, like `server.port` and `hosts.0`. When no format is set, it is guessed from the
+    /// file's extension, e.g. `.toml`, `.json`, `.yaml`, `.ini` or `.env`, falling back to the confee format
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, Format};
+    /// let mut conf = Conf::from([("server.port".to_string(), "8080".to_string())]);
+    /// conf.with_format(Format::Ini).update_from_str("[server]\nport = 9090\n").unwrap();
+    /// assert_eq!(conf["server.port"], "9090");
+    /// ```
+    pub fn with_format(&mut self, format: Format) -> &mut Self {
+        self.format = Some(format);
+        self
+    }
+    pub fn and_format(&mut self, format: Format) -> &mut Self {
+        self.with_format(format)
+    }
+    /// Gets the format update reads the configuration in
+    pub fn format(&self) -> Format {
+        #[cfg(feature = "std")]
+        if self.format.is_none() {
+            return Format::from_extension(&self.conf_file_name).unwrap_or(Format::Confee);
+        }
+        self.format.unwrap_or(Format::Confee)
     }
 
-    /// Sets the configuration file name for this Conf
+    /// Sets the configuration file name for this Conf, replacing any source or candidates set before
     pub fn with_file(&mut self, conf_file_name: &str) -> &mut Self {
         self.conf_file_name = conf_file_name.to_string();
+        self.source = None;
+        #[cfg(feature = "std")]
+        self.candidates.clear();
         self
     }
     pub fn and_file(&mut self, conf_file_name: &str) -> &mut Self {
         self.with_file(conf_file_name)
     }
-    /// Gets the configuration file name set for this Conf
+    /// Gets the configuration file name set for this Conf. After an update from candidate files, this is the
+    /// one that was chosen
     pub fn file(&self) -> &String {
         &self.conf_file_name
     }
 
-    /// Updates Conf with new values, given the file name has been set
-    /// 
+    /// Makes every successful update write the effective configuration to path, with where each value came
+    /// from and secrets masked, so the exact configuration a process ran with can be looked at later. If
+    /// writing fails, update returns the error, though the new values are applied
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn with_snapshot(&mut self, path: &str) -> &mut Self {
+        self.snapshot = Some(path.to_string());
+        self
+    }
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn and_snapshot(&mut self, path: &str) -> &mut Self {
+        self.with_snapshot(path)
+    }
+
+    /// Sets candidate configuration files for this Conf, in order of preference. update reads the first one
+    /// that exists, or layers all that exist when with_layered_files is set. Paths are expanded as by
+    /// get_path, e.g. `~/.config/app.conf` or `$XDG_CONFIG_HOME/app.conf`
+    ///
     /// # Examples
-    /// 
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_files(["./app.conf", "~/.config/app/app.conf", "/etc/app/app.conf"])
+    ///     .update()?;
+    /// println!("Loaded {}", conf.file());
+    /// # Ok::<(), confee::conf::ConfError>(())
     /// ```
-    /// let mut conf = Conf::from([
-    ///     ("foo".to_string(), "bar".to_string()),
-    ///     ("yee".to_string(), "haw".to_string()),
-    /// ]);
-    /// match conf.with_file(conf_file_name).update() {
-    ///     Ok(_) => println!("Successfully updated configuration!"),
-    ///     Err(e) => panic!("Error updating configuration: {}", e),
-    /// }
+    #[cfg(feature = "std")]
+    pub fn with_files<I, S>(&mut self, files: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.candidates = files.into_iter().map(|f| f.as_ref().to_string()).collect();
+        self.source = None;
+        self
+    }
+    #[cfg(feature = "std")]
+    pub fn and_files<I, S>(&mut self, files: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.with_files(files)
+    }
+    /// Makes update apply every candidate file that exists rather than only the first one. Files earlier in
+    /// the list take precedence over later ones
+    #[cfg(feature = "std")]
+    pub fn with_layered_files(&mut self, layered: bool) -> &mut Self {
+        self.layer_candidates = layered;
+        self
+    }
+    #[cfg(feature = "std")]
+    pub fn and_layered_files(&mut self, layered: bool) -> &mut Self {
+        self.with_layered_files(layered)
+    }
+    /// Layers configuration files for this Conf: update applies each one that exists in order, so later files
+    /// override earlier ones. Same as with_files with the order reversed and with_layered_files set
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_layers(["/etc/myapp.conf", "~/.config/myapp.conf", "./myapp.conf"])
+    ///     .update()?;
+    /// println!("port comes from {}", conf.origin("port").unwrap_or("the defaults"));
+    /// # Ok::<(), confee::conf::ConfError>(())
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_layers<I, S>(&mut self, files: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut files: Vec<S> = files.into_iter().collect();
+        files.reverse();
+        self.with_files(files).with_layered_files(true)
+    }
+    #[cfg(feature = "std")]
+    pub fn and_layers<I, S>(&mut self, files: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.with_layers(files)
+    }
+    /// Sets the platform's standard configuration files of app as candidates, most preferred first: on Linux
+    /// and other Unixes `$XDG_CONFIG_HOME/app/config`, `~/.config/app/config`, `app/config` in each of
+    /// `$XDG_CONFIG_DIRS` and `/etc/app/config`; on macOS `~/Library/Application Support/app/config` and
+    /// `/Library/Application Support/app/config` come before the Unix ones; on Windows
+    /// `%APPDATA%\app\config` then `%PROGRAMDATA%\app\config`. As with with_files, update reads the first
+    /// one that exists, or layers them all with with_layered_files
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from_defaults([("port", "8080")]);
+    /// conf.discover("myapp").update()?;
+    /// println!("Loaded {}", conf.file());
+    /// # Ok::<(), confee::conf::ConfError>(())
     /// ```
-    pub fn update(&mut self) -> Result<(), String> {
-        let lines = self.read_lines()?;
-        for line in lines {
-            let i = line
-                .find(self.delim.unwrap_or(DEFAULT_DELIM))
-                .ok_or_else(|| format!("No delimiter found in line: {}", line))?;
-            let key = line[..i].trim();
-            let value = line[i + 1..].trim();
-            self.pairs
-                .entry(key.to_string())
-                .and_modify(|v| *v = value.to_string());
+    #[cfg(feature = "std")]
+    pub fn discover(&mut self, app: &str) -> &mut Self {
+        let files = discovery_files(app);
+        self.with_files(files)
+    }
+    /// Sets a drop-in directory for this Conf, such as `/etc/myapp/conf.d`. After the file or files set, if
+    /// any, update applies every `*.conf` file in it, in name order, so later ones override earlier ones.
+    /// A missing directory holds no files. Paths are expanded as by with_files
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from_defaults([("port", "8080"), ("log", "stdout")]);
+    /// conf.with_file("/etc/myapp.conf").with_dir("/etc/myapp/conf.d").update()?;
+    /// # Ok::<(), confee::conf::ConfError>(())
+    /// ```
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn with_dir(&mut self, dir: &str) -> &mut Self {
+        self.conf_dir = Some(dir.to_string());
+        self
+    }
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn and_dir(&mut self, dir: &str) -> &mut Self {
+        self.with_dir(dir)
+    }
+    /// Selects the profile, such as `prod`, whose keys override the others. A file may then scope keys to
+    /// profiles, as in `prod.port: 80`, or `port: 80` under a `[prod]` header with sections on. Keys scoped
+    /// to the active profile win over the same keys unscoped, wherever they are in the file, and keys scoped
+    /// to other profiles are ignored. Unless set, the profile is read from the CONFEE_PROFILE environment
+    /// variable; without either, scoped keys are read as any other
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from_defaults([("port", "8080"), ("log", "stdout")]);
+    /// conf.with_profile("prod").update_from_str("prod.port: 80\ndev.log: stderr\nport: 9090").unwrap();
+    /// assert_eq!(conf["port"], "80");
+    /// assert_eq!(conf["log"], "stdout");
+    /// ```
+    pub fn with_profile(&mut self, profile: &str) -> &mut Self {
+        self.profile = Some(profile.to_string());
+        self
+    }
+    pub fn and_profile(&mut self, profile: &str) -> &mut Self {
+        self.with_profile(profile)
+    }
+    /// Gets the active profile, see with_profile
+    pub fn profile(&self) -> Option<String> {
+        #[cfg(feature = "std")]
+        if self.profile.is_none() {
+            return std::env::var(PROFILE_ENV_VAR).ok().filter(|profile| !profile.is_empty());
         }
-        self.updated = true;
-        Ok(())
+        self.profile.clone()
     }
-    fn read_lines(&self) -> Result<Vec<String>, String> {
-        let contents = fs::read_to_string(&self.conf_file_name).map_err(|e| e.to_string())?;
-        Ok(contents.lines().map(String::from).collect())
+    /// Makes update override keys with environment variables after reading the file, e.g. `port` with
+    /// `MYAPP_PORT` under prefix `MYAPP_`. Names are formed as in to_env_vars. Locked keys refuse them
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// # std::env::set_var("MYAPP_PORT", "9090");
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_str("port: 8081").with_env_prefix("MYAPP_").update().unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    /// assert_eq!(conf.origin("port"), Some("env MYAPP_PORT"));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_env_prefix(&mut self, prefix: &str) -> &mut Self {
+        self.env_prefix = Some(prefix.to_string());
+        self
     }
-
-    /// Gets the update status for this Conf
-    pub fn is_updated(&self) -> bool {
-        self.updated
+    #[cfg(feature = "std")]
+    pub fn and_env_prefix(&mut self, prefix: &str) -> &mut Self {
+        self.with_env_prefix(prefix)
     }
-
-    /// Function to index into Conf, and attempt type conversion.
+    /// Makes update override keys with command line arguments, after the file and the environment. Both
+    /// `--key=value` and `--key value` are understood. Other arguments, such as the program name, are
+    /// skipped, as is everything after `--`. Locked keys refuse them
     ///
     /// # Examples
     ///
     /// ```
+    /// # use confee::conf::Conf;
     /// let mut conf = Conf::from([
-    ///     ("addr".to_string(), "127.0.0.1".to_string()),
     ///     ("port".to_string(), "8080".to_string()),
-    ///     ("is_valid".to_string(), "true".to_string()),
+    ///     ("log".to_string(), "stdout".to_string()),
     /// ]);
-    /// let addr: IpAddr = conf.get("addr").unwrap();
-    /// let port = conf.get::<u16>("port").unwrap();
-    /// let is_valid: bool = conf.get("is_valid").unwrap();
+    /// conf.with_str("")
+    ///     .with_args(["myapp", "--port=9090", "--log", "file"])
+    ///     .update()
+    ///     .unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    /// assert_eq!(conf["log"], "file");
     /// ```
-    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
-        self.pairs.get(key).and_then(|v| v.parse::<T>().ok())
+    #[cfg(feature = "std")]
+    pub fn with_args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.args = args.into_iter().map(|arg| arg.as_ref().to_string()).collect();
+        self
+    }
+    #[cfg(feature = "std")]
+    pub fn and_args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.with_args(args)
+    }
+    /// Gets the candidate files applied by the last update, most preferred first
+    #[cfg(feature = "std")]
+    pub fn loaded_files(&self) -> &[String] {
+        &self.loaded_files
     }
-}
 
-/// Allows for the use of [ ]. Occasionally useful
-///
-/// # Examples
-///
-/// ```
-/// let mut conf = Conf::from([
-///     ("foo".to_string(), "bar".to_string()),
-///     ("yee".to_string(), "haw".to_string()),
-/// ]);
-/// println!("{}", conf["foo"]);
-/// ```
-impl Index<&str> for Conf {
-    type Output = String;
+    /// Sets the AES-256 key used to decrypt `enc:` values for this Conf. When no key is set, update falls back
+    /// to the base64 encoded key in the CONFEE_DECRYPTION_KEY environment variable
+    #[cfg(feature = "encryption")]
+    pub fn with_decryption_key(&mut self, key: [u8; 32]) -> &mut Self {
+        self.decryption_key = Some(crypto::DecryptionKey::new(key));
+        self
+    }
+    #[cfg(feature = "encryption")]
+    pub fn and_decryption_key(&mut self, key: [u8; 32]) -> &mut Self {
+        self.with_decryption_key(key)
+    }
 
-    fn index(&self, key: &str) -> &Self::Output {
-        self.pairs.get(key).unwrap_or(&self.empty_string)
+    /// Pins the ed25519 public key for this Conf. Once set, update refuses to apply the file unless the
+    /// detached signature next to it (e.g. `app.conf.sig`) verifies against this key
+    #[cfg(feature = "signing")]
+    pub fn with_public_key(&mut self, public_key: [u8; 32]) -> &mut Self {
+        self.public_key = Some(public_key);
+        self
+    }
+    #[cfg(feature = "signing")]
+    pub fn and_public_key(&mut self, public_key: [u8; 32]) -> &mut Self {
+        self.with_public_key(public_key)
     }
-}
 
-/// Displays the config file as confee would expect to read it
-///
-/// # Examples
-///
-/// ```
-/// let mut conf = Conf::from([
-///     ("foo".to_string(), "bar".to_string()),
-///     ("yee".to_string(), "haw".to_string()),
+    /// Makes update memory-map the configuration file instead of reading it, so very large files are parsed
+    /// in place without an in-memory copy. The file must not be modified while update runs
+    #[cfg(feature = "mmap")]
+    pub fn with_mmap(&mut self, mmap: bool) -> &mut Self {
+        self.mmap = mmap;
+        self
+    }
+    #[cfg(feature = "mmap")]
+    pub fn and_mmap(&mut self, mmap: bool) -> &mut Self {
+        self.with_mmap(mmap)
+    }
+
+    /// Sets the source update reads the configuration from, in place of the file. This is how content
+    /// reaches a Conf on targets without a filesystem
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_source(|| Ok("port: 9090\n".to_string())).update().unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    /// ```
+    pub fn with_source(&mut self, source: impl Source + Send + Sync + 'static) -> &mut Self {
+        self.source = Some(source::BoxedSource::new(source));
+        self
+    }
+    pub fn and_source(&mut self, source: impl Source + Send + Sync + 'static) -> &mut Self {
+        self.with_source(source)
+    }
+    /// Makes update read the configuration from reader, e.g. stdin or a network stream, in place of the
+    /// file. The reader is read to its end by the next update, so later updates find it empty unless more
+    /// arrives
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_reader(std::io::Cursor::new("port: 9090\n")).update().unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_reader(&mut self, reader: impl std::io::Read + Send + Sync + 'static) -> &mut Self {
+        self.with_source(source::ReaderSource(reader))
+    }
+    #[cfg(feature = "std")]
+    pub fn and_reader(&mut self, reader: impl std::io::Read + Send + Sync + 'static) -> &mut Self {
+        self.with_reader(reader)
+    }
+    /// Makes update read the configuration from contents, in place of the file. Handy in tests
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_str("port: 9090").update().unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    /// ```
+    pub fn with_str(&mut self, contents: &str) -> &mut Self {
+        let contents = contents.to_string();
+        self.with_source(move || Ok(contents.clone()))
+    }
+    pub fn and_str(&mut self, contents: &str) -> &mut Self {
+        self.with_str(contents)
+    }
+
+    /// Sets the metrics this Conf reports reloads, their duration, rejected updates and changed keys to
+    pub fn with_metrics(&mut self, metrics: impl ConfMetrics + 'static) -> &mut Self {
+        self.metrics = Some(metrics::Metrics::new(metrics));
+        self
+    }
+    pub fn and_metrics(&mut self, metrics: impl ConfMetrics + 'static) -> &mut Self {
+        self.with_metrics(metrics)
+    }
+
+    /// Sets caps on the input updates accept, such as the longest key or value, for configurations coming
+    /// from less-trusted sources
+    pub fn with_limits(&mut self, limits: Limits) -> &mut Self {
+        self.limits = Some(limits);
+        self
+    }
+    pub fn and_limits(&mut self, limits: Limits) -> &mut Self {
+        self.with_limits(limits)
+    }
+
+    /// Makes sure this Conf can hold at least capacity pairs without reallocating
+    pub fn with_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.pairs.reserve(capacity);
+        self
+    }
+    pub fn and_capacity(&mut self, capacity: usize) -> &mut Self {
+        self.with_capacity(capacity)
+    }
+    /// Gets the number of pairs this Conf can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        self.pairs.capacity()
+    }
+    /// Releases as much spare memory as possible, e.g. once loading is done
+    pub fn shrink_to_fit(&mut self) {
+        self.pairs.shrink_to_fit();
+        self.defaults.shrink_to_fit();
+        #[cfg(feature = "std")]
+        {
+            self.secrets.shrink_to_fit();
+            self.audited.shrink_to_fit();
+            self.locked.shrink_to_fit();
+            self.required.shrink_to_fit();
+            self.found.shrink_to_fit();
+            if let Some(interner) = &mut self.interner {
+                interner.shrink_to_fit();
+            }
+        }
+    }
+
+    /// Enables value interning for this Conf, so keys holding identical values share a single allocation.
+    /// Worth it for large generated configurations that repeat the same values over and over. Secret values
+    /// are never shared
+    pub fn with_interning(&mut self, interning: bool) -> &mut Self {
+        if !interning {
+            self.interner = None;
+            return self;
+        }
+        let interner = self.interner.get_or_insert_with(Default::default);
+        for (key, value) in self.pairs.iter_mut() {
+            if !self.secrets.contains(key) {
+                *value = interner.intern(value.as_str());
+            }
+        }
+        interner.prune();
+        self
+    }
+    pub fn and_interning(&mut self, interning: bool) -> &mut Self {
+        self.with_interning(interning)
+    }
+
+    /// Keeps the last limit values of each key, so they can be looked at with history and restored with
+    /// undo. A limit of 0 turns history off. Secret values are never kept
+    pub fn with_history(&mut self, limit: usize) -> &mut Self {
+        self.history = (limit > 0).then(|| history::History::new(limit));
+        self
+    }
+    pub fn and_history(&mut self, limit: usize) -> &mut Self {
+        self.with_history(limit)
+    }
+    /// Gets the values key held before its last changes, oldest first, along with what changed them and when
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_history(3);
+    /// conf.update_from_str("port: 9090\n").unwrap();
+    /// assert_eq!(conf.history("port")[0].value, "8080");
+    /// conf.undo("port");
+    /// assert_eq!(conf["port"], "8080");
+    /// ```
+    pub fn history(&self, key: &str) -> Vec<&Change> {
+        let key = self.canonical(key);
+        self.history.iter().flat_map(|history| history.get(key)).collect()
+    }
+    /// Restores the value key held before its last change, taking it off the history. Returns false when
+    /// there is nothing to undo
+    pub fn undo(&mut self, key: &str) -> bool {
+        let held = self.held_key(key);
+        let key = held.as_deref().unwrap_or(key);
+        let Some(change) = self.history.as_mut().and_then(|history| history.pop(key)) else {
+            return false;
+        };
+        let Some(v) = self.pairs.get_mut(key) else {
+            return false;
+        };
+        assign(v, &change.value, self.secrets.contains(key), self.interner.as_mut());
+        self.origins.insert(key.to_string(), Arc::new(provenance::Origin::other("undo")));
+        self.templates.remove(key);
+        true
+    }
+
+    /// Gives key a time to live, for values that expire such as leased credentials or short-lived tokens.
+    /// The key is stale until an update reads it, and again once ttl has passed since
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// # use std::time::Duration;
+    /// let mut conf = Conf::from([("token".to_string(), "".to_string())]);
+    /// conf.with_ttl("token", Duration::from_secs(300));
+    /// assert!(conf.is_stale("token"));
+    /// conf.update_from_str("token: abc123").unwrap();
+    /// assert!(!conf.is_stale("token"));
+    /// ```
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn with_ttl(&mut self, key: &str, ttl: std::time::Duration) -> &mut Self {
+        let key = self.canonical(key).to_string();
+        self.expiry.set(&key, ttl);
+        self
+    }
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn and_ttl(&mut self, key: &str, ttl: std::time::Duration) -> &mut Self {
+        self.with_ttl(key, ttl)
+    }
+    /// Checks whether key's time to live has run out. Keys without one never go stale
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn is_stale(&self, key: &str) -> bool {
+        self.expiry.is_stale(self.canonical(key))
+    }
+    /// Gets the keys whose time to live has run out, sorted
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn stale_keys(&self) -> Vec<&str> {
+        self.expiry.stale()
+    }
+    /// Updates Conf if any key has gone stale, so rotated credentials are picked up without a restart.
+    /// Returns whether it updated. Keys the update didn't bring back remain stale and are reported by
+    /// stale_keys
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn refresh_stale(&mut self) -> Result<bool, ConfError> {
+        if self.stale_keys().is_empty() {
+            return Ok(false);
+        }
+        self.update()?;
+        #[cfg(feature = "log")]
+        for key in self.stale_keys() {
+            log::warn!("Key {} is still stale after refreshing {}", key, self.conf_file_name);
+        }
+        Ok(true)
+    }
+
+    /// Updates Conf with new values, read from the source if one is set, otherwise from the file. Returns the
+    /// keys whose values changed, with their values before and after. An update that fails changes nothing:
+    /// every value it set already is put back as it was
+    /// 
+    /// # Examples
+    /// 
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// # let conf_file_name = "app.conf";
+    /// let mut conf = Conf::from([
+    ///     ("foo".to_string(), "bar".to_string()),
+    ///     ("yee".to_string(), "haw".to_string()),
+    /// ]);
+    /// match conf.with_file(conf_file_name).update() {
+    ///     Ok(_) => println!("Successfully updated configuration!"),
+    ///     Err(e) => panic!("Error updating configuration: {}", e),
+    /// }
+    /// ```
+    pub fn update(&mut self) -> Result<ChangeSet, ConfError> {
+        self.run_update(None, Self::load)
+    }
+    /// Same as update, but reads the configuration on a thread of its own, so async code doesn't block its
+    /// executor while waiting on the disk: the source, or the file or candidate files along with the
+    /// drop-in files and, with includes on, the files they include, and the signatures of each when a
+    /// public key is set. Only the lookups of which files exist are left to update. Works with any
+    /// executor, and pairs with watch_async for reloading. Dropping the returned future while the source is
+    /// being read drops the source too. Requires the `async` feature
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::{Conf, ConfError};
+    /// async fn reload(conf: &mut Conf) -> Result<(), ConfError> {
+    ///     conf.with_file("app.conf").update_async().await?;
+    ///     println!("port is now {}", conf["port"]);
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub async fn update_async(&mut self) -> Result<ChangeSet, ConfError> {
+        let Some(mut source) = self.source.take() else {
+            let preload = preload::Preload::of(self);
+            self.preloaded = unblock::unblock(move || preload.read()).await;
+            return self.update_preloaded(Self::load);
+        };
+        let preload = preload::Preload::signature_of(self);
+        let (source, read, preloaded) = unblock::unblock(move || {
+            let read = source.read();
+            (source, read, preload.read())
+        })
+        .await;
+        self.source = Some(source);
+        self.preloaded = preloaded;
+        self.update_preloaded(|conf| {
+            conf.load_over(|conf| {
+                let contents = read?;
+                #[cfg(feature = "zeroize")]
+                let contents = Zeroizing::new(contents);
+                conf.apply_contents(&contents)
+            })
+        })
+    }
+    // Updates Conf from the files update_async read, then forgets them
+    #[cfg(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn update_preloaded(
+        &mut self,
+        load: impl FnOnce(&mut Self) -> Result<usize, ConfError>,
+    ) -> Result<ChangeSet, ConfError> {
+        let result = self.run_update(None, load);
+        #[cfg(feature = "zeroize")]
+        self.preloaded.values_mut().for_each(|contents| contents.zeroize());
+        self.preloaded.clear();
+        result
+    }
+    /// Updates Conf with the new values found in contents, without touching the file or the source
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("host".to_string(), "localhost".to_string()),
+    /// ]);
+    /// let changes = conf.update_from_str("port: 9090\nhost: localhost\n").unwrap();
+    /// assert_eq!(conf.get::<u16>("port"), Some(9090));
+    /// assert_eq!(changes.modified().collect::<Vec<_>>(), ["port"]);
+    /// assert_eq!(changes.get("port").unwrap().to_string(), "port: 8080 -> 9090");
+    /// ```
+    pub fn update_from_str(&mut self, contents: &str) -> Result<ChangeSet, ConfError> {
+        self.run_update(Some("string"), |conf| conf.apply_contents(contents))
+    }
+    /// Same as update_from_str, for contents that arrive as raw bytes, e.g. from a fetch or a flash partition
+    pub fn update_from_bytes(&mut self, contents: &[u8]) -> Result<ChangeSet, ConfError> {
+        let contents = core::str::from_utf8(contents).map_err(|e| ConfError::utf8(contents, e))?;
+        self.update_from_str(contents)
+    }
+    /// Fetches url with the browser's fetch API and updates Conf with the new values found in the response
+    #[cfg(feature = "web")]
+    pub async fn update_from_url(&mut self, url: &str) -> Result<ChangeSet, ConfError> {
+        let contents = web::fetch(url).await?;
+        #[cfg(feature = "zeroize")]
+        let contents = Zeroizing::new(contents);
+        self.update_from_str(&contents)
+    }
+    // origin is what the history records changes as coming from, None standing for the file or the source
+    fn run_update(
+        &mut self,
+        origin: Option<&str>,
+        load: impl FnOnce(&mut Self) -> Result<usize, ConfError>,
+    ) -> Result<ChangeSet, ConfError> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "confee.update",
+            file = %self.conf_file_name,
+            keys_changed = tracing::field::Empty,
+            duration_us = tracing::field::Empty,
+        )
+        .entered();
+        self.pending_comment.clear();
+        self.section.clear();
+        self.seen.clear();
+        self.profiled.clear();
+        let origin = match origin {
+            Some(origin) => provenance::Origin::other(origin),
+            None if self.source.is_some() => provenance::Origin::source(),
+            None => provenance::Origin::file(&self.conf_file_name),
+        };
+        self.set_origin(origin);
+        self.old_values.clear();
+        let before = self.subscribers.values(&self.pairs);
+        let stopwatch = metrics::Stopwatch::start();
+        // A failed update changes nothing: the keys it changed are put back as they were, and so are the
+        // references, which would break the next update, when keys with a time to live were read and which
+        // candidates were loaded
+        let templates = self.interpolation.then(|| self.templates.clone());
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        let expiry = self.expiry.clone();
+        #[cfg(feature = "std")]
+        let candidates = (!self.candidates.is_empty())
+            .then(|| (self.conf_file_name.clone(), self.loaded_files.clone()));
+        let result = load(self).and_then(|changed| Ok(changed + self.interpolate()?));
+        if result.is_ok() {
+            self.rollback.commit(|key| self.secrets.contains(key));
+        } else {
+            self.roll_back();
+            if let Some(templates) = templates {
+                self.templates = templates;
+            }
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            {
+                self.expiry = expiry;
+            }
+            #[cfg(feature = "std")]
+            if let Some((file_name, loaded_files)) = candidates {
+                (self.conf_file_name, self.loaded_files) = (file_name, loaded_files);
+            }
+        }
+        if let Some(interner) = &mut self.interner {
+            interner.prune();
+        }
+        let old_values = core::mem::take(&mut self.old_values);
+        let elapsed = stopwatch.elapsed();
+        if let Some(metrics) = &self.metrics {
+            metrics.record(&result, elapsed);
+        }
+        #[cfg(feature = "tracing")]
+        {
+            if let Some(elapsed) = elapsed {
+                span.record("duration_us", elapsed.as_micros() as u64);
+            }
+            match &result {
+                Ok(changed) => {
+                    span.record("keys_changed", *changed);
+                }
+                Err(e) => tracing::warn!(error = %e, "configuration update failed"),
+            }
+        }
+        #[cfg(feature = "log")]
+        match &result {
+            Ok(changed) => log::info!(
+                "Updated configuration from {}, {} keys changed",
+                self.conf_file_name,
+                changed
+            ),
+            Err(e) => log::warn!("Failed to update configuration from {}: {}", self.conf_file_name, e),
+        }
+        result?;
+        self.updated = true;
+        self.subscribers.notify(&before, &self.pairs);
+        let changes = ChangeSet::since(old_values, self);
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        if let Some(snapshot) = &self.snapshot {
+            std::fs::write(snapshot, self.snapshot()).map_err(|source| ConfError::Io {
+                file: snapshot.clone(),
+                source,
+            })?;
+        }
+        Ok(changes)
+    }
+    // Notes key as it is before the running update first changes anything about it
+    fn hold(&mut self, key: &str) {
+        if self.rollback.holds(key) {
+            return;
+        }
+        let Some(value) = self.pairs.get(key) else {
+            return;
+        };
+        let held = rollback::Held {
+            value: Arc::clone(value),
+            comment: self.comments.get(key).cloned(),
+            origin: self.origins.get(key).cloned(),
+            found: self.found.contains(key),
+            history: self.history.as_ref().map(|history| history.get(key).cloned().collect()),
+        };
+        self.rollback.hold(key, held);
+    }
+    // Puts back every key the failed update changed as it was, wiping the secret values the update set
+    fn roll_back(&mut self) {
+        for (key, held) in self.rollback.take() {
+            if let Some(v) = self.pairs.get_mut(&key) {
+                #[cfg(feature = "zeroize")]
+                if let Some(owned) = Arc::get_mut(v).filter(|_| self.secrets.contains(&key)) {
+                    owned.zeroize();
+                }
+                *v = held.value;
+            }
+            match held.comment {
+                Some(comment) => self.comments.insert(key.clone(), comment),
+                None => self.comments.remove(&key),
+            };
+            match held.origin {
+                Some(origin) => self.origins.insert(key.clone(), origin),
+                None => self.origins.remove(&key),
+            };
+            if !held.found {
+                self.found.remove(&key);
+            }
+            if let (Some(history), Some(changes)) = (&mut self.history, held.history) {
+                history.restore(&key, changes);
+            }
+        }
+    }
+    // Sets what the values applied from now on come from, for the history and for snapshots
+    fn set_origin(&mut self, origin: impl Into<Arc<provenance::Origin>>) {
+        let origin = origin.into();
+        if let Some(history) = &mut self.history {
+            history.set_source(origin.label());
+        }
+        self.origin = origin;
+    }
+    // Returns how many keys changed value
+    fn load(&mut self) -> Result<usize, ConfError> {
+        self.load_over(Self::load_base)
+    }
+    // Applies base, then the environment variables and arguments overriding it
+    fn load_over<F>(&mut self, base: F) -> Result<usize, ConfError>
+    where
+        F: FnOnce(&mut Self) -> Result<usize, ConfError>,
+    {
+        let changed = base(self)?;
+        // Environment variables and arguments override profile-scoped values as any other
+        self.profiled.clear();
+        #[cfg(feature = "std")]
+        let changed = changed + self.apply_env()? + self.apply_args()?;
+        Ok(changed)
+    }
+    // Reads the source, the candidate files or the file, whichever is set
+    fn load_base(&mut self) -> Result<usize, ConfError> {
+        if let Some(source) = &mut self.source {
+            let contents = source.read()?;
+            #[cfg(feature = "zeroize")]
+            let contents = Zeroizing::new(contents);
+            return self.apply_contents(&contents);
+        }
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        if !self.candidates.is_empty() {
+            return Ok(self.load_candidates()? + self.load_dir()?);
+        }
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        if self.conf_dir.is_some() {
+            let changed = if self.conf_file_name.is_empty() { 0 } else { self.load_file()? };
+            return Ok(changed + self.load_dir()?);
+        }
+        self.load_file()
+    }
+    // Overrides keys with the environment variables named after them, when an env prefix is set
+    #[cfg(feature = "std")]
+    fn apply_env(&mut self) -> Result<usize, ConfError> {
+        let Some(prefix) = &self.env_prefix else {
+            return Ok(0);
+        };
+        let prefix = prefix.trim_end_matches('_');
+        let vars: Vec<(String, String, String)> = self
+            .pairs
+            .iter()
+            .filter_map(|(key, _)| {
+                let name = env_var_name(prefix, key);
+                if !self.case_insensitive {
+                    return std::env::var(&name).ok().map(|value| (key.clone(), name, value));
+                }
+                std::env::vars_os().find_map(|(var, value)| {
+                    let var = var.into_string().ok().filter(|var| var.eq_ignore_ascii_case(&name))?;
+                    Some((key.clone(), var, value.into_string().ok()?))
+                })
+            })
+            .collect();
+        let mut changed = 0;
+        for (key, name, value) in vars {
+            #[cfg(feature = "zeroize")]
+            let value = Zeroizing::new(value);
+            self.set_origin(provenance::Origin::env(&name));
+            changed += self.apply_pair(&key, &value, None)? as usize;
+        }
+        Ok(changed)
+    }
+    // Overrides keys with the command line arguments set with with_args
+    #[cfg(feature = "std")]
+    fn apply_args(&mut self) -> Result<usize, ConfError> {
+        if self.args.is_empty() {
+            return Ok(0);
+        }
+        let args = core::mem::take(&mut self.args);
+        let result = parse_args(&args).and_then(|pairs| {
+            let mut changed = 0;
+            for (key, value) in pairs {
+                self.set_origin(provenance::Origin::arg(key));
+                changed += self.apply_pair(key, value, None)? as usize;
+            }
+            Ok(changed)
+        });
+        self.args = args;
+        result
+    }
+    // Applies the first existing candidate, or all of them from least to most preferred when layering
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn load_candidates(&mut self) -> Result<usize, ConfError> {
+        self.loaded_files = self.resolve_candidates()?;
+        let mut changed = 0;
+        for file in self.loaded_files.clone().into_iter().rev() {
+            self.set_origin(provenance::Origin::file(&file));
+            self.section.clear();
+            self.seen.clear();
+            self.profiled.clear();
+            self.conf_file_name = file;
+            changed += self.load_file()?;
+        }
+        Ok(changed)
+    }
+    // Applies the files of the drop-in directory, in name order, leaving the file name as it was
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn load_dir(&mut self) -> Result<usize, ConfError> {
+        let files = self.dir_files()?;
+        let file_name = self.conf_file_name.clone();
+        let changed = files.into_iter().try_fold(0, |changed, file| {
+            self.set_origin(provenance::Origin::file(&file));
+            self.section.clear();
+            self.seen.clear();
+            self.profiled.clear();
+            self.conf_file_name = file;
+            Ok(changed + self.load_file()?)
+        });
+        self.conf_file_name = file_name;
+        changed
+    }
+    // The `*.conf` files in the drop-in directory, in name order
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn dir_files(&self) -> Result<Vec<String>, ConfError> {
+        match &self.conf_dir {
+            Some(dir) => drop_in_files(&expand_path(dir)),
+            None => Ok(Vec::new()),
+        }
+    }
+    // The file update reads, or every candidate
+    #[cfg(all(feature = "watch", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn base_files(&self) -> Vec<String> {
+        if self.candidates.is_empty() {
+            alloc::vec![self.conf_file_name.clone()]
+        } else {
+            self.candidates.iter().map(|file| expand_path(file)).collect()
+        }
+    }
+    // The files whose changes a watch reloads: every candidate, as one may appear or go away, or the file
+    #[cfg(all(feature = "watch", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn watched_files(&self) -> Vec<String> {
+        if self.source.is_some() {
+            return Vec::new();
+        }
+        let mut files = self.base_files();
+        files.extend(self.dir_files().unwrap_or_default());
+        files
+    }
+    // The candidates update would apply, most preferred first
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn resolve_candidates(&self) -> Result<Vec<String>, ConfError> {
+        let existing: Vec<String> = self
+            .candidates
+            .iter()
+            .map(|file| expand_path(file))
+            .filter(|file| std::path::Path::new(file).is_file())
+            .collect();
+        if existing.is_empty() {
+            return Err(ConfError::Io {
+                file: self.candidates.join(", "),
+                source: std::io::Error::new(std::io::ErrorKind::NotFound, "none of the candidate files exist"),
+            });
+        }
+        let take = if self.layer_candidates { existing.len() } else { 1 };
+        Ok(existing.into_iter().take(take).collect())
+    }
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "confee.load", skip_all, fields(file = %self.conf_file_name))
+    )]
+    fn load_file(&mut self) -> Result<usize, ConfError> {
+        #[cfg(feature = "async")]
+        // Cloned rather than taken, as a file may be included more than once
+        if let Some(contents) = self.preloaded.get(&self.conf_file_name).cloned() {
+            #[cfg(feature = "zeroize")]
+            let contents = Zeroizing::new(contents);
+            return self.apply_contents(&contents);
+        }
+        let mut file = File::open(&self.conf_file_name).map_err(|e| self.io_error(e))?;
+        #[cfg(feature = "mmap")]
+        if self.mmap {
+            // SAFETY: the mapping is only read for the duration of this call. As with any mmap, the file
+            // must not be truncated or modified by another process meanwhile, which with_mmap documents
+            let map = unsafe { memmap2::Mmap::map(&file) }.map_err(|e| self.io_error(e))?;
+            let contents = core::str::from_utf8(&map).map_err(|e| ConfError::utf8(&map, e))?;
+            return self.apply_contents(contents);
+        }
+        if self.needs_whole_file() {
+            let mut contents = String::new();
+            file.read_to_string(&mut contents).map_err(|e| self.io_error(e))?;
+            #[cfg(feature = "zeroize")]
+            let contents = Zeroizing::new(contents);
+            self.apply_contents(&contents)
+        } else {
+            let mut changed = 0;
+            let mut line_no = 0;
+            let mut reader = BufReader::new(file);
+            let mut line = String::new();
+            // Stops reading a hostile line long before it could exhaust memory
+            let max_line = self.limits.map_or(u64::MAX, |limits| limits.max_line_len());
+            while (&mut reader).take(max_line).read_line(&mut line).map_err(|e| self.io_error(e))? > 0 {
+                if line.len() as u64 == max_line && !line.ends_with('\n') {
+                    let (key, value) = match self.delims.find(&line) {
+                        Some((i, len)) => (line[..i].trim(), line[i + len..].trim()),
+                        None => (line.as_str(), ""),
+                    };
+                    let message = format!("Line longer than {} bytes", max_line);
+                    return Err(self.over_limits(key, value, message, Some(line_no + 1)));
+                }
+                line_no += 1;
+                changed += self.apply_line(line_no, line.trim_end_matches(['\r', '\n']))?;
+                line.clear();
+            }
+            Ok(changed)
+        }
+    }
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn io_error(&self, source: std::io::Error) -> ConfError {
+        ConfError::Io {
+            file: self.conf_file_name.clone(),
+            source,
+        }
+    }
+    // Without a filesystem, as on no_std targets or in the browser, there is only the source
+    #[cfg(not(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown")))))]
+    fn load_file(&mut self) -> Result<usize, ConfError> {
+        Err(ConfError::Other("No source set, and there is no filesystem to read the file from".to_string()))
+    }
+    fn apply_contents(&mut self, contents: &str) -> Result<usize, ConfError> {
+        #[cfg(feature = "signing")]
+        if let Some(public_key) = &self.public_key {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::info_span!("confee.verify_signature").entered();
+            #[cfg(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            let preloaded = self.preloaded.get(&signature::signature_file(&self.conf_file_name));
+            #[cfg(not(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown")))))]
+            let preloaded: Option<&String> = None;
+            let read;
+            let encoded = match preloaded {
+                Some(encoded) => encoded,
+                None => {
+                    read = signature::read(&self.conf_file_name)?;
+                    &read
+                }
+            };
+            signature::verify(public_key, &self.conf_file_name, encoded, contents.as_bytes())?;
+        }
+        let format = self.format();
+        let mut changed = 0;
+        if format != Format::Confee {
+            for (key, value) in format.parse(contents, self.delim())? {
+                if let Some(value) = self.deduplicate(&key, &value, None)? {
+                    changed += self.apply_pair(&key, &value, None)? as usize;
+                }
+            }
+            return Ok(changed);
+        }
+        let syntax = self.continuations.then(|| (self.delims.clone(), self.comment_chars.clone()));
+        for (line_no, line) in Self::lines(contents, syntax.as_ref()) {
+            changed += self.apply_line(line_no, &line)?;
+        }
+        Ok(changed)
+    }
+    // Other formats are parsed as a whole, as are continued lines. Signature verification covers the whole
+    // file, and secrets shouldn't pass through BufReader's internal buffer, which can't be wiped. Everything
+    // else is streamed line by line
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn needs_whole_file(&self) -> bool {
+        if self.format() != Format::Confee || self.continuations {
+            return true;
+        }
+        #[cfg(feature = "signing")]
+        if self.public_key.is_some() {
+            return true;
+        }
+        #[cfg(feature = "zeroize")]
+        if !self.secrets.is_empty() {
+            return true;
+        }
+        false
+    }
+    // Comment lines right above a key are attached to it, a blank line in between detaches them
+    fn collect_comment(&mut self, line: &str) {
+        let Some(comment) = line.trim().strip_prefix(self.comment_chars.as_slice()) else {
+            self.pending_comment.clear();
+            return;
+        };
+        if !self.pending_comment.is_empty() {
+            self.pending_comment.push('\n');
+        }
+        self.pending_comment.push_str(comment.strip_prefix(' ').unwrap_or(comment));
+    }
+    // Returns how many values changed: at most one, unless the line includes files. Allocates only when
+    // one did, and then reuses the old value's buffer when no other key shares it
+    fn apply_line(&mut self, line_no: usize, line: &str) -> Result<usize, ConfError> {
+        if let Some(section) = self.section_header(line) {
+            self.section = section.to_string();
+            self.pending_comment.clear();
+            return Ok(0);
+        }
+        let Some((key, value)) = split_line(line_no, line, &self.delims, &self.comment_chars)? else {
+            self.collect_comment(line);
+            return Ok(0);
+        };
+        let value = self.value(line_no, key, value)?;
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        if self.includes && self.section.is_empty() && self.is_include(key) {
+            self.pending_comment.clear();
+            return self.include(line_no, &value);
+        }
+        let nested;
+        let key = if self.section.is_empty() {
+            key
+        } else {
+            nested = format!("{}.{}", self.section, key);
+            &nested
+        };
+        let comment = core::mem::take(&mut self.pending_comment);
+        let Some(value) = self.deduplicate(key, &value, Some(line_no))? else {
+            return Ok(0);
+        };
+        let changed = self.apply_pair(key, &value, Some(comment)).map_err(|e| match e {
+            ConfError::UnknownKey { key, line_no: None } => ConfError::UnknownKey {
+                key,
+                line_no: Some(line_no),
+            },
+            ConfError::Invalid { key, value, message, line_no: None } => ConfError::Invalid {
+                key,
+                value,
+                message,
+                line_no: Some(line_no),
+            },
+            e => e,
+        })?;
+        Ok(changed as usize)
+    }
+    // Whether key is the one include lines are written with
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn is_include(&self, key: &str) -> bool {
+        key == "include" || self.case_insensitive && key.eq_ignore_ascii_case("include")
+    }
+    // Applies the files an include line names, each as its own file: with its own sections, and recorded
+    // as where its values came from
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn include(&mut self, line_no: usize, path: &str) -> Result<usize, ConfError> {
+        let dir = match self.origin.provenance() {
+            Provenance::File(file) => std::path::Path::new(file).parent().map(Into::into).unwrap_or_default(),
+            _ => std::path::PathBuf::new(),
+        };
+        let files = include::resolve(&dir, &expand_path(path)).map_err(|source| ConfError::Io {
+            file: path.to_string(),
+            source,
+        })?;
+        let mut changed = 0;
+        for file in files {
+            let name = file.to_string_lossy().into_owned();
+            let canonical = file.canonicalize().map_err(|source| ConfError::Io {
+                file: name.clone(),
+                source,
+            })?;
+            if self.including.contains(&canonical) {
+                return Err(ConfError::Invalid {
+                    key: "include".to_string(),
+                    value: path.to_string(),
+                    message: format!("{} is included in itself", name),
+                    line_no: Some(line_no),
+                });
+            }
+            self.including.push(canonical);
+            let origin = Arc::clone(&self.origin);
+            let file_name = core::mem::replace(&mut self.conf_file_name, name.clone());
+            let section = core::mem::take(&mut self.section);
+            self.set_origin(provenance::Origin::file(&name));
+            let result = self.load_file();
+            self.set_origin(origin);
+            (self.conf_file_name, self.section) = (file_name, section);
+            self.including.pop();
+            changed += result?;
+        }
+        Ok(changed)
+    }
+    // The value a file sets key to, given the keys it set already: value itself, value appended to the one
+    // set before, or None to keep that one. Only tracks keys when duplicates are reported or handled
+    fn deduplicate<'a>(
+        &mut self,
+        key: &str,
+        value: &'a str,
+        line_no: Option<usize>,
+    ) -> Result<Option<alloc::borrow::Cow<'a, str>>, ConfError> {
+        let held = self.held_key(key);
+        let key = held.as_deref().unwrap_or(key);
+        let tracked = self.strict || !matches!(self.duplicates, None | Some(DuplicatePolicy::LastWins));
+        if !tracked || self.pairs.get(key).is_none() || !self.seen.contains(key) {
+            if tracked {
+                self.seen.insert(key.to_string());
+            }
+            return Ok(Some(value.into()));
+        }
+        match self.duplicates {
+            Some(DuplicatePolicy::LastWins) => Ok(Some(value.into())),
+            None | Some(DuplicatePolicy::Error) => Err(ConfError::DuplicateKey {
+                key: key.to_string(),
+                line_no,
+            }),
+            Some(DuplicatePolicy::FirstWins) => Ok(None),
+            Some(DuplicatePolicy::Append(separator)) => {
+                let before = self.pairs.get(key).map_or("", |value| value.as_str());
+                Ok(Some(format!("{}{}{}", before, separator, value).into()))
+            }
+        }
+    }
+    // For a key scoped to a profile, such as `prod.port`, when a profile is active: whether the profile is
+    // the active one, and the key it scopes. Conf mustn't hold the scoped key itself, but must hold the one
+    // after the profile
+    fn profile_key<'a>(&self, key: &'a str) -> Option<(bool, &'a str)> {
+        if self.pairs.get(self.canonical(key)).is_some() {
+            return None;
+        }
+        let (profile, scoped) = key.split_once('.')?;
+        self.pairs.get(self.canonical(scoped))?;
+        Some((self.profile()? == profile, scoped))
+    }
+    // Reports that an update set key through alias
+    fn deprecated(&self, alias: &str, key: &str) {
+        #[cfg(feature = "log")]
+        log::warn!("{} in {} is deprecated, use {} instead", alias, self.conf_file_name, key);
+        if let Some(hook) = &self.deprecation_hook {
+            hook.call(alias, key);
+        }
+    }
+    // The lines of contents, joined where they continue one another when syntax gives the delimiters and
+    // comment characters to tell pairs by
+    fn lines<'a>(contents: &'a str, syntax: Option<&'a (delim::Delims, Vec<char>)>) -> lines::Lines<'a> {
+        let lines = lines::Lines::new(contents);
+        match syntax {
+            Some((delims, comment_chars)) => lines.with_continuations(delims, comment_chars),
+            None => lines,
+        }
+    }
+    // The value a line holds for key, as split from it: without its inline comment, and unquoted with
+    // quotes on
+    fn value<'a>(
+        &self,
+        line_no: usize,
+        key: &str,
+        value: &'a str,
+    ) -> Result<alloc::borrow::Cow<'a, str>, ConfError> {
+        let written = quote::written(value, self.quotes, self.inline_comments, &self.comment_chars);
+        let unquoted = match written {
+            Ok(written) if self.quotes => quote::unquote(written),
+            written => written.map(alloc::borrow::Cow::Borrowed),
+        };
+        unquoted.map_err(|message| ConfError::Parse {
+            key: key.to_string(),
+            value: if self.is_secret(key) { REDACTED.to_string() } else { value.to_string() },
+            message,
+            line_no: Some(line_no),
+        })
+    }
+    // The name in a `[section]` header line, when sections are enabled
+    fn section_header<'a>(&self, line: &'a str) -> Option<&'a str> {
+        if !self.sections {
+            return None;
+        }
+        let name = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+        Some(name.trim())
+    }
+    // Same as apply_line, for a pair that is already split. comment replaces the one attached to key,
+    // unless None
+    fn apply_pair(&mut self, key: &str, value: &str, comment: Option<String>) -> Result<bool, ConfError> {
+        let profiled = self.profile_key(key);
+        let key = match profiled {
+            Some((false, _)) => return Ok(false),
+            Some((true, key)) => key,
+            None => key,
+        };
+        let held = self.held_key(key);
+        if let Some(held) = held.as_deref().filter(|held| !held.eq_ignore_ascii_case(key)) {
+            self.deprecated(key, held);
+        }
+        let key = held.as_deref().unwrap_or(key);
+        if profiled.is_some() {
+            self.profiled.insert(key.to_string());
+        } else if self.profiled.contains(key) {
+            return Ok(false);
+        }
+        // Checked on the key held, so that neither an alias nor another case gets around the lock
+        #[cfg(feature = "std")]
+        if self.origin.is_override() && self.is_locked(key) {
+            return Err(ConfError::Locked {
+                key: key.to_string(),
+                layer: self.origin.label().to_string(),
+            });
+        }
+        if let Some(Err(message)) = self.limits.map(|limits| limits.check(key, value)) {
+            return Err(self.over_limits(key, value, message, None));
+        }
+        if self.pairs.get(key).is_none() {
+            if self.strict {
+                return Err(ConfError::UnknownKey {
+                    key: key.to_string(),
+                    line_no: None,
+                });
+            }
+            #[cfg(feature = "log")]
+            log::warn!("Ignoring unknown key {} in {}", key, self.conf_file_name);
+            return Ok(false);
+        }
+        match comment {
+            Some(comment) if comment.is_empty() && self.comments.contains_key(key) => {
+                self.hold(key);
+                self.comments.remove(key);
+            }
+            Some(comment) if comment.is_empty() => {}
+            Some(comment) if self.comments.get(key) != Some(&comment) => {
+                self.hold(key);
+                self.comments.insert(key.to_string(), comment);
+            }
+            _ => {}
+        }
+        #[cfg(feature = "encryption")]
+        let decrypted = crypto::decrypt_value(self.decryption_key.as_ref(), key, value)?;
+        #[cfg(feature = "encryption")]
+        let value: &str = &decrypted;
+        let templated = self.interpolation && value.contains("${");
+        let checked = if templated { Ok(()) } else { self.check_rules(key, value) };
+        #[cfg(all(feature = "encryption", feature = "zeroize"))]
+        if checked.is_err() {
+            if let alloc::borrow::Cow::Owned(mut plaintext) = decrypted {
+                plaintext.zeroize();
+            }
+            return checked.map(|()| false);
+        }
+        checked?;
+        if self.required.contains(key) && !self.found.contains(key) {
+            self.hold(key);
+            self.found.insert(key.to_string());
+        }
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        self.expiry.refresh(key);
+        // A reload sets most keys from where they came from already, which needn't be held
+        if self.origins.get(key).is_none_or(|origin| **origin != *self.origin) {
+            self.hold(key);
+            self.origins.insert(key.to_string(), Arc::clone(&self.origin));
+        }
+        let changed = if templated {
+            // interpolate expands, checks and assigns it once the whole update is read
+            self.templates.insert(key.to_string(), value.to_string());
+            false
+        } else {
+            self.templates.remove(key);
+            self.pairs.get(key).is_some_and(|v| v.as_str() != value)
+        };
+        if changed {
+            self.hold(key);
+            let secret = self.secrets.contains(key);
+            if let Some(v) = self.pairs.get_mut(key) {
+                if let Some(history) = self.history.as_mut().filter(|_| !secret) {
+                    history.record(key, v);
+                }
+                diff::note(&mut self.old_values, key, v, secret);
+                assign(v, value, secret, self.interner.as_mut());
+            }
+        }
+        #[cfg(all(feature = "encryption", feature = "zeroize"))]
+        if let alloc::borrow::Cow::Owned(mut plaintext) = decrypted {
+            plaintext.zeroize();
+        }
+        Ok(changed)
+    }
+    // Expands the values holding references, checking them all before assigning any. Returns how many
+    // keys changed value
+    fn interpolate(&mut self) -> Result<usize, ConfError> {
+        if self.templates.is_empty() {
+            return Ok(0);
+        }
+        let max_len = self.limits.map_or(usize::MAX, |limits| limits.max_expansion);
+        let pairs = &self.pairs;
+        let lookup = |name: &str| pairs.get(name).map(|value| value.to_string()).or_else(|| env_value(name));
+        let expanded = interpolate::Expander::new(&self.templates, lookup, max_len)
+            .expand_all()
+            .map_err(|(key, message)| ConfError::Invalid {
+                value: if self.is_secret(&key) { REDACTED.to_string() } else { self.templates[&key].clone() },
+                key,
+                message,
+                line_no: None,
+            })?;
+        for (key, value) in &expanded {
+            self.check_rules(key, value)?;
+        }
+        let mut changed = 0;
+        for (key, value) in expanded {
+            let secret = self.secrets.contains(&key);
+            let Some(v) = self.pairs.get_mut(&key) else {
+                continue;
+            };
+            if v.as_str() != value {
+                if let Some(history) = self.history.as_mut().filter(|_| !secret) {
+                    history.record(&key, v);
+                }
+                diff::note(&mut self.old_values, &key, v, secret);
+                assign(v, &value, secret, self.interner.as_mut());
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Checks what update would read, without applying any of it, and reports every problem in one pass:
+    /// malformed lines, keys Conf doesn't hold, values over the limits and values failing their rules.
+    /// Empty when there is nothing to fix
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// let problems = conf.with_str("prot: 9090\nport 9090\n").validate();
+    /// assert_eq!(problems.len(), 2);
+    /// assert_eq!(conf["port"], "8080");
+    /// ```
+    pub fn validate(&mut self) -> Vec<ConfError> {
+        if let Some(source) = &mut self.source {
+            return match source.read() {
+                Ok(contents) => self.validate_str(&contents),
+                Err(e) => alloc::vec![e],
+            };
+        }
+        self.validate_files()
+    }
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    fn validate_files(&self) -> Vec<ConfError> {
+        let mut files = if !self.candidates.is_empty() {
+            match self.resolve_candidates() {
+                Ok(files) => files,
+                Err(e) => return alloc::vec![e],
+            }
+        } else if self.conf_file_name.is_empty() && self.conf_dir.is_some() {
+            Vec::new()
+        } else {
+            alloc::vec![self.conf_file_name.clone()]
+        };
+        match self.dir_files() {
+            Ok(dir_files) => files.extend(dir_files),
+            Err(e) => return alloc::vec![e],
+        }
+        let mut problems = Vec::new();
+        for file in files {
+            match std::fs::read_to_string(&file) {
+                Ok(contents) => problems.extend(self.validate_str(&contents)),
+                Err(source) => problems.push(ConfError::Io { file, source }),
+            }
+        }
+        problems
+    }
+    #[cfg(not(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown")))))]
+    fn validate_files(&self) -> Vec<ConfError> {
+        alloc::vec![ConfError::Other("No source set, and there is no filesystem to read the file from".to_string())]
+    }
+    /// Same as validate, for the given contents
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, ConfError, Format, Limits};
+    /// let mut conf = Conf::from_defaults([("name", "")]);
+    /// conf.with_limits(Limits { max_value_len: 4, ..Limits::untrusted() });
+    /// let problems = conf.validate_str("name: confee\n");
+    /// assert!(matches!(problems[..], [ConfError::Invalid { line_no: Some(1), .. }]));
+    /// let problems = conf.with_format(Format::Ini).validate_str("name = confee\n");
+    /// assert!(matches!(problems[..], [ConfError::Invalid { line_no: None, .. }]));
+    /// ```
+    pub fn validate_str(&self, contents: &str) -> Vec<ConfError> {
+        let mut problems = Vec::new();
+        let format = self.format();
+        if format != Format::Confee {
+            match format.parse(contents, self.delim()) {
+                Ok(pairs) => {
+                    problems.extend(pairs.iter().filter_map(|(key, value)| self.check_pair(key, value, None)))
+                }
+                Err(e) => problems.push(e),
+            }
+            return problems;
+        }
+        let mut section = String::new();
+        let mut seen = alloc::collections::BTreeSet::new();
+        let syntax = self.continuations.then(|| (self.delims.clone(), self.comment_chars.clone()));
+        for (line_no, line) in Self::lines(contents, syntax.as_ref()) {
+            if let Some(name) = self.section_header(&line) {
+                section = name.to_string();
+                continue;
+            }
+            let (key, value) = match split_line(line_no, &line, &self.delims, &self.comment_chars) {
+                Ok(Some(pair)) => pair,
+                Ok(None) => continue,
+                Err(e) => {
+                    problems.push(e);
+                    continue;
+                }
+            };
+            let value = match self.value(line_no, key, value) {
+                Ok(value) => value,
+                Err(e) => {
+                    problems.push(e);
+                    continue;
+                }
+            };
+            // An include line names files rather than sets a key
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            if self.includes && section.is_empty() && self.is_include(key) {
+                continue;
+            }
+            let nested;
+            let key = if section.is_empty() {
+                key
+            } else {
+                nested = format!("{}.{}", section, key);
+                &nested
+            };
+            let reported = matches!(self.duplicates, None | Some(DuplicatePolicy::Error));
+            let key = self.canonical(key);
+            if !seen.insert(key.to_string()) && reported && self.pairs.get(key).is_some() {
+                problems.push(ConfError::DuplicateKey {
+                    key: key.to_string(),
+                    line_no: Some(line_no),
+                });
+            }
+            problems.extend(self.check_pair(key, &value, Some(line_no)));
+        }
+        problems
+    }
+    // What validate reports about a pair: an unknown key, a value over the limits or failing its rules
+    fn check_pair(&self, key: &str, value: &str, line_no: Option<usize>) -> Option<ConfError> {
+        let key = match self.profile_key(key) {
+            Some((false, _)) => return None,
+            Some((true, key)) => key,
+            None => key,
+        };
+        let key = self.canonical(key);
+        if self.pairs.get(key).is_none() {
+            return Some(ConfError::UnknownKey {
+                key: key.to_string(),
+                line_no,
+            });
+        }
+        if let Some(Err(message)) = self.limits.map(|limits| limits.check(key, value)) {
+            return Some(self.over_limits(key, value, message, line_no));
+        }
+        if self.interpolation && value.contains("${") {
+            return None;
+        }
+        self.check_rules(key, value).err().map(|e| match e {
+            ConfError::Invalid { key, value, message, .. } => ConfError::Invalid {
+                key,
+                value,
+                message,
+                line_no,
+            },
+            e => e,
+        })
+    }
+
+    /// Merges a user's configuration file with a new release of the defaults it was derived from, as when
+    /// upgrading a package. Keys the user customized keep their value, keys they left alone follow the new
+    /// defaults, and keys both sides changed differently are reported as conflicts, keeping the user's value.
+    /// Only the lines that have to change are rewritten
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let old = "port: 8080\nworkers: 4\n";
+    /// let user = "port: 9090\nworkers: 4\n";
+    /// let new = "port: 8080\nworkers: 8\ntimeout: 30\n";
+    /// let merged = Conf::merge3(old, user, new, ':').unwrap();
+    /// assert_eq!(merged.contents, "port: 9090\nworkers: 8\ntimeout: 30\n");
+    /// assert!(merged.conflicts.is_empty());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn merge3(old_defaults: &str, user_file: &str, new_defaults: &str, delim: char) -> Result<Merge3, ConfError> {
+        merge::merge3(old_defaults, user_file, new_defaults, delim)
+    }
+
+    /// Applies patch, operation by operation, as an update would. Keys the Conf doesn't hold are ignored,
+    /// unless in strict mode
+    pub fn apply_patch(&mut self, patch: &Patch) -> Result<ChangeSet, ConfError> {
+        self.run_update(Some("patch"), |conf| {
+            let mut changed = 0;
+            for (key, value) in patch.operations() {
+                changed += match value {
+                    Some(value) => conf.apply_pair(key, value, None)? as usize,
+                    None => conf.reset(key),
+                };
+            }
+            Ok(changed)
+        })
+    }
+    /// Applies overrides given as `key=value` strings, e.g. passed down by a wrapper or a test harness.
+    /// Nothing is applied if any of them lacks the `=`. Keys the Conf doesn't hold are ignored,
+    /// unless in strict mode
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("log".to_string(), "stdout".to_string()),
+    /// ]);
+    /// conf.apply_overrides(["port=9090", "log=file"]).unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    /// assert_eq!(conf["log"], "file");
+    /// ```
+    pub fn apply_overrides<I, S>(&mut self, overrides: I) -> Result<ChangeSet, ConfError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let overrides: Vec<S> = overrides.into_iter().collect();
+        let mut pairs = Vec::with_capacity(overrides.len());
+        for o in &overrides {
+            let (key, value) = o.as_ref().split_once('=').ok_or_else(|| ConfError::Parse {
+                key: o.as_ref().trim().to_string(),
+                value: String::new(),
+                message: "Expected key=value".to_string(),
+                line_no: None,
+            })?;
+            pairs.push((key.trim(), value));
+        }
+        self.run_update(Some("overrides"), |conf| {
+            let mut changed = 0;
+            for (key, value) in pairs {
+                changed += conf.apply_pair(key, value, None)? as usize;
+            }
+            Ok(changed)
+        })
+    }
+    // Resets key, and every key nested under it, to the default. Returns how many keys changed value
+    fn reset(&mut self, key: &str) -> usize {
+        let nested = |k: &str| k == key || k.strip_prefix(key).is_some_and(|rest| rest.starts_with('.'));
+        let mut changed = 0;
+        let defaults: Vec<(String, Arc<String>)> = self
+            .defaults
+            .iter()
+            .filter(|(k, _)| nested(k))
+            .map(|(k, default)| (k.clone(), Arc::clone(default)))
+            .collect();
+        for (k, default) in defaults {
+            let Some(unchanged) = self.pairs.get(&k).map(|v| v.as_str() == default.as_str()) else {
+                continue;
+            };
+            if !unchanged || self.origins.contains_key(&k) {
+                self.hold(&k);
+            }
+            self.origins.remove(k.as_str());
+            if self.interpolation && default.contains("${") {
+                self.templates.insert(k.clone(), default.to_string());
+            } else {
+                self.templates.remove(k.as_str());
+            }
+            let Some(v) = self.pairs.get_mut(&k).filter(|_| !unchanged) else {
+                continue;
+            };
+            let secret = self.secrets.contains(&k);
+            if let Some(history) = self.history.as_mut().filter(|_| !secret) {
+                history.record(&k, v);
+            }
+            diff::note(&mut self.old_values, &k, v, secret);
+            #[cfg(feature = "zeroize")]
+            if secret {
+                if let Some(owned) = Arc::get_mut(v) {
+                    owned.zeroize();
+                }
+            }
+            *v = default;
+            changed += 1;
+        }
+        changed
+    }
+
+    /// Opens the file of this Conf for editing, reading it as update does: with its delimiter, comment
+    /// characters, inline comments and sections. Saving rewrites only the values that were edited, and
+    /// leaves every other byte of the file as it was. The Conf itself only sees the new values once updated
+    /// again
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_file("app.conf");
+    /// conf.edit()?.set("port", "9090").save()?;
+    /// conf.update()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn edit(&self) -> Result<Editor, ConfError> {
+        self.editor(&self.conf_file_name, None)
+    }
+
+    /// Writes the current values back to the configuration file. See save_as
+    #[cfg(feature = "std")]
+    pub fn save(&self) -> Result<(), ConfError> {
+        self.save_as(&self.conf_file_name)
+    }
+    /// Writes the current values to path, using the delimiter of this Conf. When path already exists, the
+    /// save is lossless: only the values that changed are rewritten, in place, so comments, blank lines, key
+    /// order and inline comments survive. Keys the file lacks are added, under their `[section]` when
+    /// sections are on, unless they hold their default. Values taken from environment variables, command
+    /// line arguments or drop-in files aren't written, and encrypted `enc:` values are left as written. The
+    /// file is replaced atomically, through a temporary file, and keeps its permissions; see Editor::save.
+    /// Files in other formats, set by with_format or guessed from path's extension, are rewritten as a whole
+    /// with export
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// # let dir = std::env::temp_dir().join(format!("confee-save-{}", std::process::id()));
+    /// # std::fs::create_dir_all(&dir).unwrap();
+    /// # let path = dir.join("app.conf");
+    /// # let path = path.to_str().unwrap();
+    /// std::fs::write(path, "# Server\n[server]\nport: 8080 # the usual\n\n[log]\nlevel: info\n").unwrap();
+    /// let mut conf = Conf::from([
+    ///     ("server.port".to_string(), "8080".to_string()),
+    ///     ("server.host".to_string(), "localhost".to_string()),
+    ///     ("log.level".to_string(), "info".to_string()),
+    /// ]);
+    /// conf.with_file(path).with_sections(true).with_inline_comments(true).update()?;
+    /// conf.set("server.port", "9090").set("server.host", "example.com").save()?;
+    /// assert_eq!(
+    ///     std::fs::read_to_string(path).unwrap(),
+    ///     "# Server\n[server]\nport: 9090 # the usual\nhost: example.com\n\n[log]\nlevel: info\n"
+    /// );
+    /// # std::fs::remove_dir_all(&dir).unwrap();
+    /// # Ok::<(), confee::conf::ConfError>(())
+    /// ```
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_file("app.conf").update()?;
+    /// conf.set("port", "9090").save()?;
+    /// conf.save_as("backup.conf")?;
+    /// conf.save_as("backup.json")?;
+    /// # Ok::<(), confee::conf::ConfError>(())
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn save_as(&self, path: &str) -> Result<(), ConfError> {
+        let format = self.format.or_else(|| Format::from_extension(path)).unwrap_or(Format::Confee);
+        if format != Format::Confee {
+            return Editor::from_contents(path, &self.export(format), self.delims.clone()).save();
+        }
+        let exists = std::path::Path::new(path).exists();
+        let contents = if exists { None } else { Some("") };
+        let mut editor = self.editor(path, contents)?;
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        let drop_ins = self.dir_files().unwrap_or_default();
+        for (key, value) in self.pairs.sorted() {
+            // Into an existing file go only the values it doesn't hold yet: not the overrides of this run,
+            // nor defaults it leaves out
+            let overridden = match self.provenance(key) {
+                Some(Provenance::Env(_) | Provenance::Arg(_)) => true,
+                #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+                Some(Provenance::File(file)) => drop_ins.iter().any(|drop_in| drop_in == file),
+                _ => false,
+            };
+            match editor.get(key) {
+                _ if exists && overridden => {}
+                Some(old) if old.starts_with("enc:") => {}
+                Some(old) if self.value(0, key, old).is_ok_and(|old| old == value.as_str()) => {}
+                None if exists && self.defaults.get(key) == Some(value) => {}
+                _ => {
+                    editor.set(key, &self.written(value));
+                }
+            }
+        }
+        editor.save()
+    }
+    // value as save writes it, so that update reads it back the same: quoted if need be, or else with its
+    // lines indented as continuations
+    #[cfg(feature = "std")]
+    fn written<'a>(&self, value: &'a str) -> alloc::borrow::Cow<'a, str> {
+        if self.quotes {
+            quote::quote(value, if self.inline_comments { &self.comment_chars } else { &[] })
+        } else if self.continuations && value.contains('\n') {
+            value.replace('\n', "\n    ").into()
+        } else {
+            value.into()
+        }
+    }
+    // An Editor for path reading it as this Conf does, over contents if given rather than the file's
+    #[cfg(feature = "std")]
+    fn editor(&self, path: &str, contents: Option<&str>) -> Result<Editor, ConfError> {
+        let editor = match contents {
+            Some(contents) => Editor::from_contents(path, contents, self.delims.clone()),
+            None => Editor::open_with(path, self.delims.clone())?,
+        };
+        let (inline, quotes, continuations) = (self.inline_comments, self.quotes, self.continuations);
+        let editor = editor.with_syntax(&self.comment_chars, inline, self.sections, quotes, continuations);
+        Ok(editor.with_ignore_case(self.case_insensitive))
+    }
+
+    /// Watches the configuration file of conf, or its candidate files, checking every interval whether any
+    /// was modified. When one was, conf is updated, and on_change is given it along with the keys that
+    /// changed. on_change runs while conf is locked for writing, so it should be quick. A failed update
+    /// leaves conf as it was; it is logged and counted in the metrics like any other, and the next change is
+    /// tried again. Confs reading a source aren't watched. Watching stops when the returned Watcher is
+    /// dropped. Requires the `watch` feature
+    ///
+    /// The files are polled from a thread of the watch's own, rather than watched through file system
+    /// events, which would take a dependency such as notify. A change is picked up within interval, and each
+    /// check costs a metadata lookup per file, in the background. A file is seen as modified when its
+    /// modification time or its length differs, so on file systems with coarse timestamps, a change keeping
+    /// the length that lands within the same tick as the one before may go unnoticed
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// # use std::sync::{Arc, RwLock};
+    /// # use std::time::Duration;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_file("app.conf").update()?;
+    /// let conf = Arc::new(RwLock::new(conf));
+    /// let _watcher = Conf::watch(Arc::clone(&conf), Duration::from_secs(2), |_, changes| {
+    ///     for change in changes {
+    ///         println!("{}", change);
+    ///     }
+    /// });
+    /// # Ok::<(), confee::conf::ConfError>(())
+    /// ```
+    #[cfg(all(feature = "watch", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn watch<F>(
+        conf: std::sync::Arc<std::sync::RwLock<Conf>>,
+        interval: std::time::Duration,
+        on_change: F,
+    ) -> Watcher
+    where
+        F: FnMut(&Conf, &ChangeSet) + Send + 'static,
+    {
+        watch::spawn(conf, interval, on_change)
+    }
+    /// Watches the files update reads, as watch does, for async code: the returned AsyncWatcher's changed
+    /// resolves whenever any was modified, without blocking the executor, and leaves reloading to the
+    /// caller, e.g. with update_async. Drop-in files are looked for anew on every check. Confs reading a
+    /// source aren't watched. Works with any executor. Requires the `async` feature
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::{Conf, ConfError};
+    /// # use std::time::Duration;
+    /// async fn reload_forever(mut conf: Conf) -> Result<(), ConfError> {
+    ///     conf.with_file("app.conf").update_async().await?;
+    ///     let mut watcher = conf.watch_async(Duration::from_secs(2));
+    ///     loop {
+    ///         watcher.changed().await;
+    ///         match conf.update_async().await {
+    ///             Ok(changes) => changes.iter().for_each(|change| println!("{}", change)),
+    ///             Err(e) => eprintln!("Reload failed, keeping the current configuration: {}", e),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[cfg(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn watch_async(&self, interval: std::time::Duration) -> AsyncWatcher {
+        let files = if self.source.is_some() { Vec::new() } else { self.base_files() };
+        let dir = self.conf_dir.as_deref().map(expand_path).filter(|_| self.source.is_none());
+        let watched = move || {
+            let mut watched = files.clone();
+            if let Some(dir) = &dir {
+                watched.extend(drop_in_files(dir).unwrap_or_default());
+            }
+            watched
+        };
+        watch::spawn_async(watched, interval)
+    }
+
+    /// Updates conf whenever the process receives SIGHUP, the usual way of telling a daemon to re-read its
+    /// configuration. The outcome of each reload, the keys that changed or why it failed, is sent to the
+    /// returned channel for the main loop to act on. Signals arriving during a reload are folded into one
+    /// more. Only one handler can be installed per process. Requires the `sighup` feature, on Unix
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// # use std::sync::{Arc, RwLock};
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_file("/etc/app.conf").update()?;
+    /// let conf = Arc::new(RwLock::new(conf));
+    /// let reloads = Conf::reload_on_sighup(Arc::clone(&conf))?;
+    /// for reload in reloads {
+    ///     match reload {
+    ///         Ok(changes) if changes.contains("port") => println!("port: {}", conf.read().unwrap()["port"]),
+    ///         Ok(_) => {}
+    ///         Err(e) => eprintln!("Reload failed, keeping the current configuration: {}", e),
+    ///     }
+    /// }
+    /// # Ok::<(), confee::conf::ConfError>(())
+    /// ```
+    #[cfg(all(unix, feature = "sighup"))]
+    pub fn reload_on_sighup(
+        conf: std::sync::Arc<std::sync::RwLock<Conf>>,
+    ) -> Result<std::sync::mpsc::Receiver<Result<ChangeSet, ConfError>>, ConfError> {
+        sighup::install(conf)
+    }
+
+    /// Gets the update status for this Conf
+    pub fn is_updated(&self) -> bool {
+        self.updated
+    }
+    /// Compares Conf with other, e.g. a staged configuration with the live one. The change set holds the
+    /// keys only other holds as added, the keys only Conf holds as removed, and the keys whose values differ
+    /// as modified. Values of keys either side marks secret are masked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let live = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("legacy".to_string(), "on".to_string()),
+    /// ]);
+    /// let mut staged = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("workers".to_string(), "4".to_string()),
+    /// ]);
+    /// staged.set("port", "9090");
+    /// let changes = live.diff(&staged);
+    /// assert_eq!(changes.added().collect::<Vec<_>>(), ["workers"]);
+    /// assert_eq!(changes.removed().collect::<Vec<_>>(), ["legacy"]);
+    /// assert_eq!(changes.modified().collect::<Vec<_>>(), ["port"]);
+    /// ```
+    pub fn diff(&self, other: &Conf) -> ChangeSet {
+        ChangeSet::between(self, other)
+    }
+
+    /// Sets the value of key, adding the key if Conf doesn't hold it yet, e.g. after normalizing a value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("dir".to_string(), "/var/www".to_string())]);
+    /// conf.set("dir", "/var/www/").set("mode", "fast");
+    /// assert_eq!(conf["dir"], "/var/www/");
+    /// assert!(conf.contains_key("mode"));
+    /// assert_eq!(conf.remove("mode"), Some("fast".to_string()));
+    /// assert!(!conf.contains_key("mode"));
+    /// ```
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        let held = self.held_key(key);
+        let key = held.as_deref().unwrap_or(key);
+        self.set_origin(provenance::Origin::other("set"));
+        self.templates.remove(key);
+        if key.starts_with(SECRET_PREFIX) && !self.secrets.contains(key) {
+            self.secrets.insert(key.to_string());
+        }
+        let secret = self.secrets.contains(key);
+        match self.pairs.get_mut(key) {
+            Some(v) if v.as_str() == value => {}
+            Some(v) => {
+                if let Some(history) = self.history.as_mut().filter(|_| !secret) {
+                    history.record(key, v);
+                }
+                assign(v, value, secret, self.interner.as_mut());
+            }
+            None => {
+                let mut v = Arc::new(String::new());
+                assign(&mut v, value, secret, self.interner.as_mut());
+                self.pairs.insert(key.to_string(), v);
+            }
+        }
+        self.origins.insert(key.to_string(), Arc::clone(&self.origin));
+        self
+    }
+    /// Removes key, returning its value if Conf held it. Later updates ignore the key, as they do any key
+    /// Conf doesn't hold
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from_defaults([("port", "8080"), ("log", "stdout")]);
+    /// conf.with_case_insensitive(true);
+    /// assert_eq!(conf.remove("PORT").as_deref(), Some("8080"));
+    /// assert!(!conf.contains_key("port"));
+    /// ```
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let held = self.held_key(key);
+        let key = held.as_deref().unwrap_or(key);
+        let value = self.pairs.remove(key)?;
+        self.origins.remove(key);
+        self.templates.remove(key);
+        Some(Arc::try_unwrap(value).unwrap_or_else(|shared| shared.as_str().to_string()))
+    }
+    /// Checks whether Conf holds key
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.pairs.get(self.canonical(key)).is_some()
+    }
+    /// Gets how many keys Conf holds
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+    /// Checks whether Conf holds no keys at all
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Iterates over the keys and values, in no particular order. Values of audited keys are reported to
+    /// the audit hook as they are reached, as get does. Sort the pairs first for a stable order
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("host".to_string(), "localhost".to_string()),
+    /// ]);
+    /// let mut pairs: Vec<(&str, &str)> = conf.iter().collect();
+    /// pairs.sort();
+    /// assert_eq!(pairs, [("host", "localhost"), ("port", "8080")]);
+    /// assert_eq!(conf.len(), 2);
+    /// ```
+    #[track_caller]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(self, Location::caller())
+    }
+    /// Iterates over the keys, in no particular order
+    pub fn keys(&self) -> Keys<'_> {
+        Keys(self.pairs.iter())
+    }
+    /// Iterates over the values, in no particular order. Values of audited keys are reported to the audit
+    /// hook, as with iter
+    #[track_caller]
+    pub fn values(&self) -> Values<'_> {
+        Values(self.iter())
+    }
+
+    /// Marks key as holding a secret value, such as a password or token. Display, Debug and the redacted
+    /// renderings print `****` in its place. With the `zeroize` feature enabled, secret values are wiped
+    /// from memory when overwritten by update or when Conf is dropped. Keys starting with `secret.` are
+    /// secret without being marked
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([
+    ///     ("user".to_string(), "admin".to_string()),
+    ///     ("password".to_string(), "hunter2".to_string()),
+    ///     ("secret.token".to_string(), "abc123".to_string()),
+    /// ]);
+    /// conf.mark_secret("password");
+    /// assert!(conf.is_secret("secret.token"));
+    /// let shown = format!("{} {:?}", conf, conf);
+    /// assert!(!shown.contains("hunter2") && !shown.contains("abc123"));
+    /// assert!(shown.contains("user: admin"));
+    /// ```
+    pub fn mark_secret(&mut self, key: &str) -> &mut Self {
+        let key = self.canonical(key).to_string();
+        self.secrets.insert(key);
+        self
+    }
+    /// Checks whether key has been marked as secret
+    pub fn is_secret(&self, key: &str) -> bool {
+        self.secrets.contains(self.canonical(key))
+    }
+
+    /// Locks key, so only the defaults and the configuration file may set it. Lower-trust override layers,
+    /// such as environment variables or command line arguments, are refused for locked keys, even when they
+    /// name them through an alias or, with case-insensitive keys, in another case
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, ConfError};
+    /// let mut conf = Conf::from_defaults([("port", "8080")]);
+    /// conf.alias("listen_port", "port").lock("port").with_str("");
+    /// let refused = conf.with_args(["myapp", "--listen_port=1"]).update();
+    /// assert!(matches!(refused, Err(ConfError::Locked { .. })));
+    /// conf.with_case_insensitive(true).with_args(["myapp", "--PORT=2"]);
+    /// assert!(matches!(conf.update(), Err(ConfError::Locked { .. })));
+    /// assert_eq!(conf["port"], "8080");
+    /// ```
+    pub fn lock(&mut self, key: &str) -> &mut Self {
+        let key = self.canonical(key).to_string();
+        self.locked.insert(key);
+        self
+    }
+    /// Checks whether key has been locked
+    pub fn is_locked(&self, key: &str) -> bool {
+        self.locked.contains(self.canonical(key))
+    }
+
+    /// Makes alias another name for key, so configuration files still using an old name keep working. An
+    /// update setting key through alias reports it to the deprecation hook, and logs a warning with the
+    /// `log` feature, so users can be told to rename it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from_defaults([("port", "8080")]);
+    /// conf.alias("listen_port", "port").with_deprecation_hook(|alias, key| {
+    ///     eprintln!("{} is deprecated, use {} instead", alias, key);
+    /// });
+    /// conf.update_from_str("listen_port: 9090").unwrap();
+    /// assert_eq!(conf["port"], "9090");
+    /// assert_eq!(conf["listen_port"], "9090");
+    /// ```
+    pub fn alias(&mut self, alias: &str, key: &str) -> &mut Self {
+        let key = self.canonical(key).to_string();
+        self.aliases.insert(alias.to_string(), key);
+        self
+    }
+    /// Sets the callback invoked when an update sets a key through one of its aliases, with the alias and
+    /// the key
+    pub fn with_deprecation_hook(&mut self, hook: impl Fn(&str, &str) + Send + Sync + 'static) -> &mut Self {
+        self.deprecation_hook = Some(alias::DeprecationHook::new(hook));
+        self
+    }
+    pub fn and_deprecation_hook(&mut self, hook: impl Fn(&str, &str) + Send + Sync + 'static) -> &mut Self {
+        self.with_deprecation_hook(hook)
+    }
+
+    /// Documents key, next to its default. The description is shown by to_string_commented and template,
+    /// when prompting for the key, and in errors about it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.describe("port", "TCP port the HTTP listener binds to");
+    /// assert_eq!(conf.to_string_commented(), "# TCP port the HTTP listener binds to\nport: 8080\n");
+    /// ```
+    pub fn describe(&mut self, key: &str, description: &str) -> &mut Self {
+        let key = self.canonical(key).to_string();
+        self.descriptions.insert(key, description.to_string());
+        self
+    }
+    /// Gets the description of key, if it has been described
+    pub fn description(&self, key: &str) -> Option<&str> {
+        self.descriptions.get(self.canonical(key)).map(String::as_str)
+    }
+
+    /// Gets the comment written right above key in the file, without the leading `#`. Comments spanning
+    /// several lines are joined with newlines
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.update_from_str("# Behind the load balancer\nport: 9090\n").unwrap();
+    /// assert_eq!(conf.comment("port"), Some("Behind the load balancer"));
+    /// ```
+    pub fn comment(&self, key: &str) -> Option<&str> {
+        self.comments.get(self.canonical(key)).map(String::as_str)
+    }
+
+    /// Marks key as required, meaning an update is expected to set it rather than leave the default in place
+    pub fn require(&mut self, key: &str) -> &mut Self {
+        let key = self.canonical(key).to_string();
+        self.required.insert(key);
+        self
+    }
+    /// Adds a validation rule for key. Values that fail it are refused by update, and reported by validate
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, Rule};
+    /// let mut conf = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("log".to_string(), "stdout".to_string()),
+    /// ]);
+    /// conf.require("port")
+    ///     .with_rule("port", Rule::new().expect_type::<u16>().range(1..=65535))
+    ///     .with_rule("log", Rule::new().one_of(&["stdout", "file"]));
+    /// assert!(conf.update_from_str("log: syslog").is_err());
+    /// ```
+    pub fn with_rule(&mut self, key: &str, validator: impl Validator + 'static) -> &mut Self {
+        let key = self.canonical(key).to_string();
+        self.validators.add(&key, validator);
+        self
+    }
+    pub fn and_rule(&mut self, key: &str, validator: impl Validator + 'static) -> &mut Self {
+        self.with_rule(key, validator)
+    }
+    // Runs the rules for key on value, masking value in the error if key is secret
+    fn check_rules(&self, key: &str, value: &str) -> Result<(), ConfError> {
+        self.validators.validate(key, value).map_err(|message| ConfError::Invalid {
+            key: key.to_string(),
+            value: if self.is_secret(key) { REDACTED.to_string() } else { value.to_string() },
+            message,
+            line_no: None,
+        })
+    }
+    // The error for key and value breaking the limits: cut short where they're too long to repeat, and with
+    // the value masked for secret keys
+    fn over_limits(&self, key: &str, value: &str, message: String, line_no: Option<usize>) -> ConfError {
+        let limits = self.limits.unwrap_or_default();
+        let value = if self.is_secret(key) {
+            REDACTED.to_string()
+        } else {
+            limits::excerpt(value, limits.max_value_len)
+        };
+        ConfError::Invalid {
+            key: limits::excerpt(key, limits.max_key_len),
+            value,
+            message,
+            line_no,
+        }
+    }
+    /// Checks whether key has been marked as required
+    pub fn is_required(&self, key: &str) -> bool {
+        self.required.contains(self.canonical(key))
+    }
+    /// Gets the required keys no update has set so far, sorted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([
+    ///     ("user".to_string(), "".to_string()),
+    ///     ("port".to_string(), "8080".to_string()),
+    /// ]);
+    /// conf.require("user").require("port");
+    /// conf.update_from_str("port: 9090\n").unwrap();
+    /// assert_eq!(conf.missing_keys(), ["user"]);
+    /// ```
+    pub fn missing_keys(&self) -> Vec<&str> {
+        let mut missing: Vec<&str> = self
+            .required
+            .iter()
+            .filter(|key| !self.found.contains(*key) && self.pairs.get(key).is_some())
+            .map(String::as_str)
+            .collect();
+        missing.sort();
+        missing
+    }
+
+    /// Asks on the terminal for the value of every missing required key, showing its default, and hiding
+    /// the input for secret keys. With write_back, the answers are also saved to the file, which is created
+    /// if needed. Fails, listing the missing keys, when stdin is not a terminal
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("api_token".to_string(), "".to_string())]);
+    /// conf.with_file("app.conf").require("api_token").mark_secret("api_token");
+    /// conf.update().unwrap();
+    /// conf.prompt_missing(true).unwrap();
+    /// ```
+    #[cfg(feature = "interactive")]
+    pub fn prompt_missing(&mut self, write_back: bool) -> Result<(), ConfError> {
+        let missing: Vec<String> = self.missing_keys().into_iter().map(String::from).collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        if !prompt::is_interactive() {
+            let keys = missing
+                .iter()
+                .map(|key| match self.description(key) {
+                    Some(description) => format!("{} ({})", key, description),
+                    None => key.to_string(),
+                })
+                .collect();
+            return Err(ConfError::MissingKeys { keys });
+        }
+        self.set_origin(provenance::Origin::other("prompt"));
+        let mut answers = Vec::new();
+        for key in missing {
+            let secret = self.is_secret(&key);
+            let default = self.pairs.get(&key).map(|v| v.as_str()).unwrap_or_default();
+            let default = if secret && !default.is_empty() { REDACTED } else { default };
+            if let Some(answer) = prompt::ask(&key, self.description(&key), default, secret)? {
+                #[cfg(feature = "zeroize")]
+                let answer = Zeroizing::new(answer);
+                if let Some(v) = self.pairs.get_mut(&key) {
+                    if let Some(history) = self.history.as_mut().filter(|_| !secret) {
+                        history.record(&key, v);
+                    }
+                    assign(v, &answer, secret, self.interner.as_mut());
+                    self.origins.insert(key.clone(), Arc::clone(&self.origin));
+                }
+                answers.push((key.clone(), answer));
+            }
+            self.found.insert(key);
+        }
+        if write_back && !answers.is_empty() {
+            if !std::path::Path::new(&self.conf_file_name).exists() {
+                std::fs::write(&self.conf_file_name, "").map_err(|source| ConfError::Io {
+                    file: self.conf_file_name.clone(),
+                    source,
+                })?;
+            }
+            let mut editor = self.editor(&self.conf_file_name, None)?;
+            for (key, answer) in &answers {
+                editor.set(key, answer);
+            }
+            editor.save()?;
+        }
+        Ok(())
+    }
+
+    /// Subscribes callback to changes of key's value. After each successful update that changes it, callback
+    /// is called with the old value and the new one, so a subsystem can react to just the keys it uses. A
+    /// failed update changes nothing and calls no callback; the next update that succeeds reports the change
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// # use std::sync::{Arc, Mutex};
+    /// let mut conf = Conf::from([
+    ///     ("log_level".to_string(), "info".to_string()),
+    ///     ("port".to_string(), "8080".to_string()),
+    /// ]);
+    /// let changes = Arc::new(Mutex::new(Vec::new()));
+    /// let seen = Arc::clone(&changes);
+    /// conf.on_change("log_level", move |old, new| seen.lock().unwrap().push(format!("{} -> {}", old, new)));
+    /// conf.update_from_str("port: 9090").unwrap();
+    /// conf.update_from_str("log_level: debug").unwrap();
+    /// assert_eq!(*changes.lock().unwrap(), ["info -> debug"]);
+    /// ```
+    pub fn on_change(&mut self, key: &str, callback: impl Fn(&str, &str) + Send + Sync + 'static) -> &mut Self {
+        let key = self.canonical(key).to_string();
+        self.subscribers.add(&key, callback);
+        self
+    }
+
+    /// Marks key as audited, so every read of it through get or [ ] is reported to the audit hook
+    pub fn mark_audited(&mut self, key: &str) -> &mut Self {
+        let key = self.canonical(key).to_string();
+        self.audited.insert(key);
+        self
+    }
+    /// Sets the callback invoked on each read of an audited key, with the key, the caller's source location
+    /// and the time of access
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("db_password".to_string(), "hunter2".to_string())]);
+    /// conf.mark_audited("db_password").with_audit_hook(|event| {
+    ///     eprintln!("{} read at {}", event.key, event.location);
+    /// });
+    /// let password: String = conf.get("db_password").unwrap();
+    /// ```
+    pub fn with_audit_hook(&mut self, hook: impl Fn(&AccessEvent) + Send + Sync + 'static) -> &mut Self {
+        self.audit_hook = Some(audit::AuditHook::new(hook));
+        self
+    }
+    pub fn and_audit_hook(&mut self, hook: impl Fn(&AccessEvent) + Send + Sync + 'static) -> &mut Self {
+        self.with_audit_hook(hook)
+    }
+    /// Gets the keys that were read through get, get_in or try_get without existing, sorted, so stale key
+    /// names left in code after renaming keys can be found. In debug builds each comes with the closest
+    /// existing key. Reads through get_or, get_or_else or [ ], which expect keys to be absent, aren't
+    /// recorded, and recording stops once 64 keys are missed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// let port: Option<u16> = conf.get("prot");
+    /// let missed = conf.missed_keys();
+    /// assert_eq!(missed[0].key, "prot");
+    /// if let Some(suggestion) = &missed[0].suggestion {
+    ///     eprintln!("unknown key prot, did you mean {}?", suggestion);
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn missed_keys(&self) -> Vec<MissedKey> {
+        self.misses
+            .keys()
+            .into_iter()
+            .map(|key| MissedKey {
+                #[cfg(debug_assertions)]
+                suggestion: miss::closest(&key, self.pairs.iter().map(|(k, _)| k.as_str()))
+                    .map(String::from),
+                #[cfg(not(debug_assertions))]
+                suggestion: None,
+                key,
+            })
+            .collect()
+    }
+    // Looks key up on behalf of the getters and [ ], reporting the read to the audit hook
+    fn lookup(&self, key: &str, location: &'static Location<'static>) -> Option<&Arc<String>> {
+        let key = self.canonical(key);
+        self.audit(key, location);
+        self.pairs.get(key)
+    }
+    // Same as lookup, recording a miss, for get and try_get. Getters given a default expect keys to be
+    // absent, so their misses aren't worth recording
+    fn lookup_or_miss(&self, key: &str, location: &'static Location<'static>) -> Option<&Arc<String>> {
+        let value = self.lookup(key, location);
+        #[cfg(feature = "std")]
+        if value.is_none() {
+            self.misses.record(self.canonical(key));
+        }
+        value
+    }
+    // The key Conf holds for key: key itself, the key it is an alias of, or with case-insensitive keys, the
+    // key or alias it matches ignoring case
+    fn canonical<'a>(&'a self, key: &'a str) -> &'a str {
+        if self.pairs.get(key).is_some() {
+            return key;
+        }
+        if let Some(held) = self.aliases.get(key) {
+            return held;
+        }
+        if !self.case_insensitive {
+            return key;
+        }
+        let keys = self.pairs.iter().map(|(k, _)| (k, k));
+        let mut names = keys.chain(&self.aliases);
+        names.find(|(name, _)| name.eq_ignore_ascii_case(key)).map_or(key, |(_, held)| held)
+    }
+    // Same as canonical, for methods that go on to change Conf, and so can't hold on to the key: None when
+    // key is the one Conf holds
+    fn held_key(&self, key: &str) -> Option<String> {
+        let canonical = self.canonical(key);
+        (canonical != key).then(|| canonical.to_string())
+    }
+    fn audit(&self, key: &str, location: &'static Location<'static>) {
+        if let Some(hook) = &self.audit_hook {
+            if self.audited.contains(key) {
+                hook.call(key, location);
+            }
+        }
+    }
+
+    /// Exports Conf in the given format, sorted by key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, Format};
+    /// let conf = Conf::from([
+    ///     ("foo".to_string(), "bar".to_string()),
+    ///     ("yee".to_string(), "haw".to_string()),
+    /// ]);
+    /// assert_eq!(conf.export(Format::DotEnv), "foo=bar\nyee=haw\n");
+    /// ```
+    pub fn export(&self, format: Format) -> String {
+        self.export_pairs(format, false)
+    }
+    /// Renders Conf as a JSON object of its keys and string values, sorted by key, for tooling that
+    /// consumes the effective configuration. Use export_redacted with Format::Json to mask secrets
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("name".to_string(), "say \"hi\"".to_string()),
+    /// ]);
+    /// assert_eq!(conf.to_json(), "{\n  \"name\": \"say \\\"hi\\\"\",\n  \"port\": \"8080\"\n}\n");
+    /// ```
+    pub fn to_json(&self) -> String {
+        self.export(Format::Json)
+    }
+    /// Renders Conf sorted by key, with the values of secret keys masked as Display does, so the output can
+    /// be attached to bug reports safely
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([
+    ///     ("user".to_string(), "admin".to_string()),
+    ///     ("password".to_string(), "hunter2".to_string()),
+    /// ]);
+    /// conf.mark_secret("password");
+    /// assert!(conf.to_string_redacted().contains("password: ****"));
+    /// ```
+    pub fn to_string_redacted(&self) -> String {
+        self.export_redacted(Format::Confee)
+    }
+    /// Renders Conf like Display does, sorted by key, with each described key preceded by its description
+    /// as a comment. Handy for generating a documented sample configuration from the defaults. Secret values
+    /// are masked
+    pub fn to_string_commented(&self) -> String {
+        let pairs = self.pairs.sorted();
+        let mut out = String::new();
+        for (key, value) in pairs {
+            if let Some(description) = self.description(key) {
+                for line in description.lines() {
+                    out.push_str(&format!("# {}\n", line));
+                }
+            }
+            let value = if self.is_secret(key) { REDACTED } else { value.as_str() };
+            out.push_str(&format!("{}{} {}\n", key, self.delims, value));
+        }
+        out
+    }
+    /// Renders a commented configuration file holding the defaults, sorted by key, for users to start
+    /// from. Each key is preceded by its description, and by a note when it is required or secret. Secret
+    /// defaults are left out, so the template holds no credentials, and reading it back gives the defaults
+    /// otherwise
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("token".to_string(), "".to_string()),
+    /// ]);
+    /// conf.describe("port", "TCP port to listen on").describe("token", "API token").require("token");
+    /// let expected = "# TCP port to listen on\nport: 8080\n\n# API token\n# Required\ntoken:\n";
+    /// assert_eq!(conf.template(), expected);
+    /// ```
+    pub fn template(&self) -> String {
+        let comment = self.comment_chars.first().copied().unwrap_or('#');
+        let mut out = String::new();
+        for (key, default) in self.defaults.sorted() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            for line in self.description(key).into_iter().flat_map(str::lines) {
+                out.push_str(&format!("{} {}\n", comment, line));
+            }
+            if self.is_required(key) {
+                out.push_str(&format!("{} Required\n", comment));
+            }
+            let default = if self.is_secret(key) {
+                out.push_str(&format!("{} Secret\n", comment));
+                ""
+            } else {
+                default.as_str()
+            };
+            let pair = format!("{}{} {}", key, self.delims, default);
+            out.push_str(pair.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+    /// Writes template to path, e.g. for an `--init-config` flag. Fails rather than overwrite a file that
+    /// already exists
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.describe("port", "TCP port to listen on");
+    /// if std::env::args().any(|arg| arg == "--init-config") {
+    ///     conf.write_template("app.conf")?;
+    /// }
+    /// # Ok::<(), confee::conf::ConfError>(())
+    /// ```
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub fn write_template(&self, path: &str) -> Result<(), ConfError> {
+        use std::io::Write;
+        let io_error = |source| ConfError::Io {
+            file: path.to_string(),
+            source,
+        };
+        let mut file = std::fs::OpenOptions::new().write(true).create_new(true).open(path).map_err(io_error)?;
+        file.write_all(self.template().as_bytes()).map_err(io_error)
+    }
+    /// Gets where key's value came from: the file or layer that set it, `env` and the variable name, `arg`
+    /// and the flag, or `source`, `string`, `patch`, `overrides`, `prompt`, `set` or `undo`. None while it
+    /// holds its default
+    pub fn origin(&self, key: &str) -> Option<&str> {
+        self.origins.get(self.canonical(key)).map(|origin| origin.label())
+    }
+    /// Gets where key's value came from, telling the default, files, environment variables, command line
+    /// arguments and the source apart. None if Conf doesn't hold key
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, Provenance};
+    /// let mut conf = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("host".to_string(), "localhost".to_string()),
+    ///     ("log".to_string(), "stdout".to_string()),
+    /// ]);
+    /// conf.with_str("host: example.com").with_args(["myapp", "--port=9090"]).update().unwrap();
+    /// assert_eq!(conf.provenance("port"), Some(Provenance::Arg("port")));
+    /// assert_eq!(conf.provenance("host"), Some(Provenance::Source));
+    /// assert_eq!(conf.provenance("log"), Some(Provenance::Default));
+    /// assert_eq!(conf.provenance("missing"), None);
+    /// ```
+    pub fn provenance(&self, key: &str) -> Option<Provenance<'_>> {
+        let key = self.canonical(key);
+        self.pairs.get(key)?;
+        Some(self.origins.get(key).map_or(Provenance::Default, |origin| origin.provenance()))
+    }
+    /// Renders the effective configuration sorted by key, each value preceded by a comment naming where it
+    /// came from, with secret values masked. This is what with_snapshot writes after every update
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("password".to_string(), "".to_string()),
+    /// ]);
+    /// conf.mark_secret("password");
+    /// conf.update_from_str("password: hunter2").unwrap();
+    /// assert_eq!(conf.snapshot(), "# from string\npassword: ****\n# from default\nport: 8080\n");
+    /// ```
+    pub fn snapshot(&self) -> String {
+        let pairs = self.pairs.sorted();
+        let mut out = String::new();
+        for (key, value) in pairs {
+            let origin = self.origins.get(key).map_or("default", |origin| origin.label());
+            let value = if self.is_secret(key) { REDACTED } else { value.as_str() };
+            out.push_str(&format!("# from {}\n{}{} {}\n", origin, key, self.delims, value));
+        }
+        out
+    }
+    /// Exports Conf in the given format, with the values of secret keys masked
+    pub fn export_redacted(&self, format: Format) -> String {
+        self.export_pairs(format, true)
+    }
+    fn export_pairs(&self, format: Format, redact: bool) -> String {
+        let mut pairs: Vec<(&str, &str)> = self
+            .pairs
+            .iter()
+            .map(|(key, value)| {
+                let value = if redact && self.is_secret(key) { REDACTED } else { value.as_str() };
+                (key.as_str(), value)
+            })
+            .collect();
+        pairs.sort();
+        format.write(&pairs, self.delim_str())
+    }
+
+    /// Turns the effective (key, value) pairs into environment variables, sorted by name. Names are the key
+    /// prefixed with `prefix_`, upper-cased, with every character that isn't a letter or a digit replaced by
+    /// an underscore, so `server.port` under prefix `app` becomes `APP_SERVER_PORT`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from([("server.port".to_string(), "8080".to_string())]);
+    /// assert_eq!(conf.to_env_vars("app"), [("APP_SERVER_PORT".to_string(), "8080".to_string())]);
+    /// ```
+    pub fn to_env_vars(&self, prefix: &str) -> Vec<(String, String)> {
+        let mut vars: Vec<(String, String)> = self
+            .pairs
+            .iter()
+            .map(|(key, value)| (env_var_name(prefix, key), value.to_string()))
+            .collect();
+        vars.sort();
+        vars
+    }
+    /// Renders the effective configuration as an env file, for `docker run --env-file` or a systemd
+    /// EnvironmentFile. Variables are named as by to_env_vars, and values are double quoted only when they
+    /// hold characters that aren't taken literally
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from([
+    ///     ("server.port".to_string(), "8080".to_string()),
+    ///     ("motd".to_string(), "hello world".to_string()),
+    /// ]);
+    /// assert_eq!(conf.to_env_file("app"), "APP_MOTD=\"hello world\"\nAPP_SERVER_PORT=8080\n");
+    /// ```
+    pub fn to_env_file(&self, prefix: &str) -> String {
+        let vars = self.to_env_vars(prefix);
+        let pairs: Vec<(&str, &str)> = vars.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        Format::DotEnv.write(&pairs, self.delim_str())
+    }
+    /// Injects the effective (key, value) pairs into command as environment variables, named as by
+    /// to_env_vars, so a spawned worker sees the same configuration
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// # use std::process::Command;
+    /// let conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// let child = conf.apply_to(&mut Command::new("worker"), "app").spawn();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn apply_to<'c>(&self, command: &'c mut Command, prefix: &str) -> &'c mut Command {
+        command.envs(self.to_env_vars(prefix))
+    }
+
+    /// Asserts that the effective configuration matches the golden file at path, which holds the expected
+    /// (key, value) pairs in the confee format. Order, comments and blank lines in the file don't matter. On
+    /// mismatch, panics with a diff of the pairs. With the CONFEE_BLESS environment variable set, the file
+    /// is (re)written from the Conf instead
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Conf;
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// conf.with_file("tests/app.conf").update().unwrap();
+    /// conf.assert_matches_file("tests/golden/app.conf");
+    /// ```
+    #[cfg(feature = "testing")]
+    #[track_caller]
+    pub fn assert_matches_file(&self, path: &str) {
+        if std::env::var_os("CONFEE_BLESS").is_some() {
+            if let Err(e) = std::fs::write(path, self.export(Format::Confee)) {
+                panic!("Could not write golden file {}: {}", path, e);
+            }
+            return;
+        }
+        let expected = match std::fs::read_to_string(path) {
+            Ok(expected) => expected,
+            Err(e) => panic!("Could not read golden file {}: {} (set CONFEE_BLESS to create it)", path, e),
+        };
+        let expected = expected.lines().enumerate();
+        let expected =
+            expected.filter_map(|(i, line)| split_line(i + 1, line, &self.delims, DEFAULT_COMMENT_CHARS).transpose());
+        let expected: BTreeMap<&str, &str> = match expected.collect() {
+            Ok(pairs) => pairs,
+            Err(e) => panic!("Malformed golden file {}: {}", path, e),
+        };
+        let actual: BTreeMap<&str, &str> = self.pairs.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        let keys: alloc::collections::BTreeSet<&str> = expected.keys().chain(actual.keys()).copied().collect();
+        let mut diff = String::new();
+        for key in keys {
+            let (expected, actual) = (expected.get(key), actual.get(key));
+            if expected == actual {
+                continue;
+            }
+            if let Some(expected) = expected {
+                diff.push_str(&format!("- {}{} {}\n", key, self.delims, expected));
+            }
+            if let Some(actual) = actual {
+                diff.push_str(&format!("+ {}{} {}\n", key, self.delims, actual));
+            }
+        }
+        if !diff.is_empty() {
+            panic!("Configuration does not match golden file {} (- expected, + actual):\n{}", path, diff);
+        }
+    }
+
+    /// Computes a stable hash of the effective (key, value) pairs. The result does not depend on
+    /// insertion order, and stays the same across runs, platforms and Rust versions
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from([
+    ///     ("foo".to_string(), "bar".to_string()),
+    ///     ("yee".to_string(), "haw".to_string()),
+    /// ]);
+    /// println!("config version: {:016x}", conf.fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint_pairs(|_| true)
+    }
+    /// Same as fingerprint, but leaves out keys marked as secret, so the hash can be published freely
+    pub fn fingerprint_without_secrets(&self) -> u64 {
+        self.fingerprint_pairs(|key| !self.is_secret(key))
+    }
+    fn fingerprint_pairs(&self, include: impl Fn(&str) -> bool) -> u64 {
+        let mut pairs: Vec<(&String, &String)> = self
+            .pairs
+            .iter()
+            .filter(|(k, _)| include(k))
+            .map(|(k, v)| (k, &**v))
+            .collect();
+        pairs.sort();
+        pairs
+            .into_iter()
+            .flat_map(|(k, v)| [k.as_bytes(), &[0], v.as_bytes(), &[0]])
+            .flatten()
+            .fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ *byte as u64).wrapping_mul(FNV_PRIME))
+    }
+
+    /// Splits the keys numbered under prefix, such as `upstream.1.host` and `upstream.2.host`, into one Conf
+    /// per number, in numeric order, holding the keys below it, e.g. `host`. The delimiter and secret marks
+    /// carry over
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from([
+    ///     ("upstream.1.host".to_string(), "10.0.0.1".to_string()),
+    ///     ("upstream.2.host".to_string(), "10.0.0.2".to_string()),
+    ///     ("upstream.2.weight".to_string(), "3".to_string()),
+    /// ]);
+    /// let upstreams = conf.indexed("upstream");
+    /// assert_eq!(upstreams.len(), 2);
+    /// assert_eq!(upstreams[1]["host"], "10.0.0.2");
+    /// ```
+    pub fn indexed(&self, prefix: &str) -> Vec<Conf> {
+        let mut groups: BTreeMap<u64, Vec<(&str, &str, bool)>> = BTreeMap::new();
+        for (key, value) in self.pairs.iter() {
+            let Some(rest) = key.strip_prefix(prefix).and_then(|rest| rest.strip_prefix('.')) else {
+                continue;
+            };
+            let Some((index, sub_key)) = rest.split_once('.') else {
+                continue;
+            };
+            if let Ok(index) = index.parse::<u64>() {
+                groups.entry(index).or_default().push((sub_key, value, self.is_secret(key)));
+            }
+        }
+        groups.into_values().map(|pairs| self.scoped(&pairs)).collect()
+    }
+    /// Gets the keys nested under prefix as a Conf of their own, with the prefix stripped, e.g. to hand just
+    /// the `db.*` keys to a database module. The values are the current ones, and become the new Conf's
+    /// defaults. The delimiter and secret keys carry over
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from([
+    ///     ("db.url".to_string(), "postgres://localhost".to_string()),
+    ///     ("db.pool.max".to_string(), "16".to_string()),
+    ///     ("port".to_string(), "8080".to_string()),
+    /// ]);
+    /// let db = conf.subtree("db");
+    /// assert_eq!(db.get::<u32>("pool.max"), Some(16));
+    /// assert!(!db.contains_key("port"));
+    /// ```
+    pub fn subtree(&self, prefix: &str) -> Conf {
+        let pairs: Vec<(&str, &str, bool)> = self
+            .pairs
+            .iter()
+            .filter_map(|(key, value)| {
+                let sub_key = key.strip_prefix(prefix)?.strip_prefix('.')?;
+                Some((sub_key, value.as_str(), self.is_secret(key)))
+            })
+            .collect();
+        self.scoped(&pairs)
+    }
+    // A Conf holding pairs, with this one's delimiter, in which the keys flagged as secret are marked so
+    fn scoped(&self, pairs: &[(&str, &str, bool)]) -> Conf {
+        let mut conf: Conf = pairs.iter().map(|(k, v, _)| (k.to_string(), v.to_string())).collect();
+        conf.delims = self.delims.clone();
+        for (key, _, _) in pairs.iter().filter(|(_, _, secret)| *secret) {
+            conf.mark_secret(key);
+        }
+        conf
+    }
+    /// Same as indexed, converting each Conf to T, e.g. a struct describing one backend
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// struct Upstream {
+    ///     host: String,
+    ///     weight: u32,
+    /// }
+    ///
+    /// impl TryFrom<&Conf> for Upstream {
+    ///     type Error = String;
+    ///
+    ///     fn try_from(conf: &Conf) -> Result<Self, Self::Error> {
+    ///         Ok(Upstream {
+    ///             host: conf.get("host").ok_or("missing host")?,
+    ///             weight: conf.get("weight").unwrap_or(1),
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let conf = Conf::from([
+    ///     ("upstream.1.host".to_string(), "10.0.0.1".to_string()),
+    ///     ("upstream.2.host".to_string(), "10.0.0.2".to_string()),
+    ///     ("upstream.2.weight".to_string(), "3".to_string()),
+    /// ]);
+    /// let upstreams: Vec<Upstream> = conf.bind_indexed("upstream").unwrap();
+    /// assert_eq!(upstreams[1].weight, 3);
+    /// ```
+    pub fn bind_indexed<T, E>(&self, prefix: &str) -> Result<Vec<T>, E>
+    where
+        T: for<'a> TryFrom<&'a Conf, Error = E>,
+    {
+        self.indexed(prefix).iter().map(T::try_from).collect()
+    }
+
+    /// Function to index into Conf, and attempt type conversion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// # use std::net::IpAddr;
+    /// let mut conf = Conf::from([
+    ///     ("addr".to_string(), "127.0.0.1".to_string()),
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("is_valid".to_string(), "true".to_string()),
+    /// ]);
+    /// let addr: IpAddr = conf.get("addr").unwrap();
+    /// let port = conf.get::<u16>("port").unwrap();
+    /// let is_valid: bool = conf.get("is_valid").unwrap();
+    /// ```
+    #[track_caller]
+    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.lookup_or_miss(key, Location::caller()).and_then(|v| v.parse::<T>().ok())
+    }
+    /// Same as get, with default in place of None: when key is absent, including keys Conf doesn't know, or
+    /// its value doesn't parse
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from_defaults([("port", "9090"), ("workers", "many")]);
+    /// assert_eq!(conf.get_or::<u16>("port", 8080), 9090);
+    /// assert_eq!(conf.get_or::<u16>("workers", 4), 4);
+    /// assert_eq!(conf.get_or("timeout", 30), 30);
+    /// ```
+    #[track_caller]
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> T {
+        self.lookup(key, Location::caller()).and_then(|v| v.parse::<T>().ok()).unwrap_or(default)
+    }
+    /// Same as get_or, computing the default only when it's needed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from_defaults([("port", "8080")]);
+    /// let workers = conf.get_or_else("workers", || 2 * 4);
+    /// assert_eq!(workers, 8);
+    /// ```
+    #[track_caller]
+    pub fn get_or_else<T: FromStr>(&self, key: &str, default: impl FnOnce() -> T) -> T {
+        self.lookup(key, Location::caller()).and_then(|v| v.parse::<T>().ok()).unwrap_or_else(default)
+    }
+    /// Gets the value of key as a list, split on the list separator, with each item trimmed and parsed into
+    /// T. Empty items are skipped, so an empty value gives an empty list. None when key is absent or an
+    /// item doesn't parse
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// # use std::net::IpAddr;
+    /// let mut conf = Conf::from([
+    ///     ("hosts".to_string(), "10.0.0.1, 10.0.0.2,10.0.0.3".to_string()),
+    ///     ("paths".to_string(), "/usr/bin:/bin".to_string()),
+    /// ]);
+    /// let hosts: Vec<IpAddr> = conf.get_vec("hosts").unwrap();
+    /// assert_eq!(hosts.len(), 3);
+    /// conf.with_list_separator(':');
+    /// assert_eq!(conf.get_vec::<String>("paths").unwrap(), ["/usr/bin", "/bin"]);
+    /// ```
+    #[track_caller]
+    pub fn get_vec<T: FromStr>(&self, key: &str) -> Option<Vec<T>> {
+        let value = self.lookup(key, Location::caller())?;
+        split_list(value, self.list_separator).map(|item| item.parse::<T>().ok()).collect()
+    }
+    /// Gets the value of key as a boolean, accepting the spellings people use in configuration files: true,
+    /// yes, on, y and 1, or false, no, off, n and 0, in any case. None when key is absent or the value is
+    /// none of them
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from([
+    ///     ("enabled".to_string(), "Yes".to_string()),
+    ///     ("verbose".to_string(), "off".to_string()),
+    /// ]);
+    /// assert_eq!(conf.get_bool("enabled"), Some(true));
+    /// assert_eq!(conf.get_bool("verbose"), Some(false));
+    /// ```
+    #[track_caller]
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        parse_bool(self.lookup(key, Location::caller())?)
+    }
+    /// Gets the value of key as a duration written with units, such as `30s`, `5m`, `1h30m` or `1.5h`. The
+    /// units are ns, us, ms, s, m, h, d and w, and a bare number counts seconds. None when key is absent or
+    /// the value doesn't parse
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// # use std::time::Duration;
+    /// let conf = Conf::from([("timeout".to_string(), "1h30m".to_string())]);
+    /// assert_eq!(conf.get_duration("timeout"), Some(Duration::from_secs(90 * 60)));
+    /// ```
+    #[track_caller]
+    pub fn get_duration(&self, key: &str) -> Option<core::time::Duration> {
+        units::parse_duration(self.lookup(key, Location::caller())?).ok()
+    }
+    /// Gets the value of key as a number of bytes, written with an optional unit such as `10MB` or `512KiB`.
+    /// KB, MB, GB, TB and PB are powers of 1000, KiB, MiB, GiB, TiB and PiB, or K, M, G, T and P, powers of
+    /// 1024. Units are case-insensitive. None when key is absent or the value doesn't parse
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// let conf = Conf::from([
+    ///     ("max_upload".to_string(), "10MB".to_string()),
+    ///     ("buffer".to_string(), "512KiB".to_string()),
+    /// ]);
+    /// assert_eq!(conf.get_bytes("max_upload"), Some(10_000_000));
+    /// assert_eq!(conf.get_bytes("buffer"), Some(512 * 1024));
+    /// ```
+    #[track_caller]
+    pub fn get_bytes(&self, key: &str) -> Option<u64> {
+        units::parse_bytes(self.lookup(key, Location::caller())?).ok()
+    }
+    /// Gets the value of key as a path, with a leading `~` or `~user` expanded to the home directory and
+    /// `$VAR` or `${VAR}` to the environment variable's value, so `dir: ~/www` gives an absolute path.
+    /// Anything that can't be expanded is left as written
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// # use std::path::PathBuf;
+    /// let conf = Conf::from([("dir".to_string(), "~/www".to_string())]);
+    /// # std::env::set_var("HOME", "/home/alice");
+    /// assert_eq!(conf.get_path("dir"), Some(PathBuf::from("/home/alice/www")));
+    /// ```
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[track_caller]
+    pub fn get_path(&self, key: &str) -> Option<std::path::PathBuf> {
+        self.lookup(key, Location::caller()).map(|v| std::path::PathBuf::from(expand_path(v)))
+    }
+    /// Deserializes Conf into T, e.g. a struct with a field per key, parsing each value into the field's
+    /// type. Dotted keys such as `server.port` fill nested structs, numbered keys such as `hosts.0` and
+    /// list values, split as by get_vec, fill sequences, and an empty value gives None. Requires the `serde`
+    /// feature
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// # use std::net::IpAddr;
+    /// # use std::path::PathBuf;
+    /// #[derive(serde::Deserialize)]
+    /// struct MyConfig {
+    ///     port: u16,
+    ///     addr: IpAddr,
+    ///     dir: PathBuf,
+    ///     tls: Tls,
+    ///     hosts: Vec<String>,
+    ///     timeout: Option<u32>,
+    /// }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Tls {
+    ///     enabled: bool,
+    /// }
+    ///
+    /// let conf = Conf::from([
+    ///     ("port".to_string(), "8080".to_string()),
+    ///     ("addr".to_string(), "127.0.0.1".to_string()),
+    ///     ("dir".to_string(), "/var/www".to_string()),
+    ///     ("tls.enabled".to_string(), "true".to_string()),
+    ///     ("hosts".to_string(), "a.example, b.example".to_string()),
+    ///     ("timeout".to_string(), "".to_string()),
+    /// ]);
+    /// let config: MyConfig = conf.to_struct().unwrap();
+    /// assert_eq!(config.port, 8080);
+    /// assert!(config.tls.enabled);
+    /// assert_eq!(config.hosts, ["a.example", "b.example"]);
+    /// assert_eq!(config.timeout, None);
+    /// ```
+    #[cfg(feature = "serde")]
+    #[track_caller]
+    pub fn to_struct<T: serde::de::DeserializeOwned>(&self) -> Result<T, ConfError> {
+        T::deserialize(de::Node::root(self, Location::caller()))
+    }
+    /// Same as get, for key in section, i.e. for `section.key`
+    #[track_caller]
+    pub fn get_in<T: FromStr>(&self, section: &str, key: &str) -> Option<T> {
+        self.lookup_or_miss(&format!("{}.{}", section, key), Location::caller())
+            .and_then(|v| v.parse::<T>().ok())
+    }
+    /// Same as get, but tells an absent key apart from a value that doesn't parse, for error messages users
+    /// can act on. The values of secret keys are masked in the error
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, ConfError};
+    /// let conf = Conf::from([("port".to_string(), "abc".to_string())]);
+    /// assert!(matches!(conf.try_get::<u16>("port"), Err(ConfError::Parse { .. })));
+    /// assert!(matches!(conf.try_get::<u16>("prot"), Err(ConfError::UnknownKey { .. })));
+    /// ```
+    #[track_caller]
+    pub fn try_get<T>(&self, key: &str) -> Result<T, ConfError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let value = self
+            .lookup_or_miss(key, Location::caller())
+            .ok_or_else(|| ConfError::UnknownKey {
+                key: key.to_string(),
+                line_no: None,
+            })?;
+        value.parse::<T>().map_err(|e| ConfError::Parse {
+            key: key.to_string(),
+            value: if self.is_secret(key) { REDACTED.to_string() } else { value.to_string() },
+            message: e.to_string(),
+            line_no: None,
+        })
+    }
+}
+
+/// Parses every (key, value) pair in input, in order, without filtering them against any defaults.
+/// Useful for tooling that needs to look at a file as written rather than at its effect on a Conf
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::parse_pairs;
+/// let pairs = parse_pairs("foo: bar\nyee: haw\n", ':').unwrap();
+/// assert_eq!(pairs, [("foo", "bar"), ("yee", "haw")]);
+/// ```
+pub fn parse_pairs(input: &str, delim: char) -> Result<Vec<(&str, &str)>, ConfError> {
+    let delims = delim::Delims::from(delim);
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| split_line(i + 1, line, &delims, DEFAULT_COMMENT_CHARS).transpose())
+        .collect()
+}
+
+/// Replaces the value in v. Allocates only when needed, reusing the old value's buffer when no other key
+/// shares it
+fn assign(v: &mut Arc<String>, value: &str, secret: bool, interner: Option<&mut intern::Interner>) {
+    match interner {
+        Some(interner) if !secret => *v = interner.intern(value),
+        _ => match Arc::get_mut(v) {
+            Some(owned) => {
+                #[cfg(feature = "zeroize")]
+                if secret {
+                    owned.zeroize();
+                }
+                owned.clear();
+                owned.push_str(value);
+            }
+            None => *v = Arc::new(value.to_string()),
+        },
+    }
+}
+
+/// Parses the usual spellings of a boolean, see Conf::get_bool
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "y" | "1" => Some(true),
+        "false" | "no" | "off" | "n" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Splits a list value into its trimmed, non-empty items
+fn split_list(value: &str, separator: char) -> impl Iterator<Item = &str> {
+    value.split(separator).map(str::trim).filter(|item| !item.is_empty())
+}
+
+/// Gets the value of environment variable name, for interpolation. There is no environment without std
+fn env_value(name: &str) -> Option<String> {
+    #[cfg(feature = "std")]
+    return std::env::var(name).ok();
+    #[cfg(not(feature = "std"))]
+    {
+        let _ = name;
+        None
+    }
+}
+
+/// Builds the environment variable name for key, see Conf::to_env_vars
+fn env_var_name(prefix: &str, key: &str) -> String {
+    let name = if prefix.is_empty() { key.to_string() } else { format!("{}_{}", prefix, key) };
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+/// Collects the `--key=value` and `--key value` pairs out of command line arguments
+#[cfg(feature = "std")]
+fn parse_args(args: &[String]) -> Result<Vec<(&str, &str)>, ConfError> {
+    let mut pairs = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            break;
+        }
+        let Some(flag) = arg.strip_prefix("--") else {
+            continue;
+        };
+        match flag.split_once('=') {
+            Some((key, value)) => pairs.push((key, value)),
+            None => {
+                let value = args
+                    .next()
+                    .filter(|value| !value.starts_with("--"))
+                    .ok_or_else(|| ConfError::Parse {
+                        key: flag.to_string(),
+                        value: String::new(),
+                        message: format!("Missing value after {}", arg),
+                        line_no: None,
+                    })?;
+                pairs.push((flag, value.as_str()));
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+// Cuts a trailing comment off value, i.e. a comment character preceded by whitespace and everything after it
+fn strip_inline_comment<'a>(value: &'a str, comment_chars: &[char]) -> &'a str {
+    let mut prev_space = false;
+    for (i, c) in value.char_indices() {
+        if prev_space && comment_chars.contains(&c) {
+            return value[..i].trim_end();
+        }
+        prev_space = c.is_whitespace();
+    }
+    value
+}
+
+/// Splits a line into its trimmed key and value around the first delimiter in it. Blank lines and
+/// comment lines, which start with one of comment_chars, hold no pair
+fn split_line<'a>(
+    line_no: usize,
+    line: &'a str,
+    delims: &delim::Delims,
+    comment_chars: &[char],
+) -> Result<Option<(&'a str, &'a str)>, ConfError> {
+    let trimmed = line.trim_start();
+    if trimmed.is_empty() || trimmed.starts_with(comment_chars) {
+        return Ok(None);
+    }
+    let (i, len) = delims.find(line).ok_or_else(|| ConfError::MissingDelimiter {
+        line_no,
+        line: line.to_string(),
+    })?;
+    Ok(Some((line[..i].trim(), line[i + len..].trim())))
+}
+
+/// Wipes secret values from memory, so they don't linger in freed heap allocations
+#[cfg(feature = "zeroize")]
+impl Drop for Conf {
+    fn drop(&mut self) {
+        // Defaults first: a value that is still the default is shared, and only wiped once unique
+        for key in &self.secrets {
+            for store in [&mut self.defaults, &mut self.pairs] {
+                if let Some(value) = store.get_mut(key) {
+                    let mut value = core::mem::take(value);
+                    if let Some(value) = Arc::get_mut(&mut value) {
+                        value.zeroize();
+                    }
+                }
+            }
+        }
+    }
+}
+
+// The standard configuration files of app on this platform, most preferred first. Directories from
+// variables that are unset, empty or relative are left out, as the XDG specification asks
+#[cfg(feature = "std")]
+fn discovery_files(app: &str) -> Vec<String> {
+    let var = |name: &str| {
+        let dir = std::path::PathBuf::from(std::env::var_os(name)?);
+        dir.is_absolute().then_some(dir)
+    };
+    let mut dirs = Vec::new();
+    if cfg!(windows) {
+        dirs.extend(var("APPDATA"));
+        dirs.extend(var("PROGRAMDATA"));
+    } else {
+        let home = var("HOME");
+        if cfg!(target_os = "macos") {
+            dirs.extend(home.as_ref().map(|home| home.join("Library/Application Support")));
+            dirs.push("/Library/Application Support".into());
+        }
+        dirs.extend(var("XDG_CONFIG_HOME"));
+        dirs.extend(home.map(|home| home.join(".config")));
+        let system = std::env::var_os("XDG_CONFIG_DIRS").unwrap_or_default();
+        dirs.extend(std::env::split_paths(&system).filter(|dir| dir.is_absolute()));
+        dirs.push("/etc".into());
+    }
+    let mut files: Vec<String> = Vec::new();
+    for dir in dirs {
+        let file = dir.join(app).join("config").to_string_lossy().into_owned();
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    files
+}
+
+// The `*.conf` files in dir, in name order
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+fn drop_in_files(dir: &str) -> Result<Vec<String>, ConfError> {
+    let files = include::resolve(std::path::Path::new(dir), "*.conf").map_err(|source| ConfError::Io {
+        file: dir.to_string(),
+        source,
+    })?;
+    Ok(files.iter().map(|file| file.to_string_lossy().into_owned()).collect())
+}
+
+// Expands a leading `~` or `~user` to a home directory, and `$VAR` or `${VAR}` to the variable's value.
+// Whatever can't be expanded is left as written
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+fn expand_path(path: &str) -> String {
+    let mut expanded = String::with_capacity(path.len());
+    let mut rest = path;
+    if let Some(after) = path.strip_prefix('~') {
+        let end = after.find(['/', '\\']).unwrap_or(after.len());
+        if let Some(home) = home_dir(&after[..end]) {
+            expanded.push_str(&home);
+            rest = &after[end..];
+        }
+    }
+    while let Some(i) = rest.find('$') {
+        expanded.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let (name, next) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], &braced[end + 1..]),
+                None => ("", after),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        match std::env::var(name).ok().filter(|_| !name.is_empty()) {
+            Some(value) => {
+                expanded.push_str(&value);
+                rest = next;
+            }
+            None => {
+                expanded.push('$');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+// The home directory of user, or of the current user when user is empty
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+fn home_dir(user: &str) -> Option<String> {
+    let own = std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")).ok();
+    if user.is_empty() {
+        return own;
+    }
+    #[cfg(unix)]
+    {
+        let _ = own;
+        let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+        passwd.lines().find_map(|line| {
+            let fields: Vec<&str> = line.split(':').collect();
+            (fields.len() > 5 && fields[0] == user).then(|| fields[5].to_string())
+        })
+    }
+    // Elsewhere, home directories are assumed to sit side by side
+    #[cfg(not(unix))]
+    {
+        let own = std::path::PathBuf::from(own?);
+        Some(own.parent()?.join(user).to_string_lossy().into_owned())
+    }
+}
+
+/// Creates a Conf from defaults only known at runtime, e.g. read from a defaults file
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::Conf;
+/// let keys = ["foo", "yee"];
+/// let conf: Conf = keys.iter().map(|k| (k.to_string(), String::new())).collect();
+/// ```
+impl FromIterator<(String, String)> for Conf {
+    fn from_iter<I: IntoIterator<Item = (String, String)>>(defaults: I) -> Self {
+        let pairs: store::Store<String, Arc<String>> = defaults
+            .into_iter()
+            .map(|(k, v)| (k, Arc::new(v)))
+            .collect();
+        let secrets = pairs.iter().map(|(k, _)| k).filter(|k| k.starts_with(SECRET_PREFIX));
+        let secrets = secrets.cloned().collect();
+        Self {
+            defaults: pairs.clone(),
+            pairs,
+            delims: delim::Delims::default(),
+            comment_chars: DEFAULT_COMMENT_CHARS.to_vec(),
+            inline_comments: false,
+            quotes: false,
+            continuations: false,
+            list_separator: ',',
+            strict: false,
+            interpolation: false,
+            templates: BTreeMap::new(),
+            old_values: BTreeMap::new(),
+            rollback: Default::default(),
+            sections: false,
+            section: String::new(),
+            case_insensitive: false,
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            includes: false,
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            including: Vec::new(),
+            duplicates: None,
+            seen: KeySet::new(),
+            profile: None,
+            profiled: KeySet::new(),
+            format: None,
+            conf_file_name: "".to_string(),
+            #[cfg(feature = "std")]
+            candidates: Vec::new(),
+            #[cfg(feature = "std")]
+            layer_candidates: false,
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            conf_dir: None,
+            #[cfg(feature = "std")]
+            loaded_files: Vec::new(),
+            #[cfg(feature = "std")]
+            env_prefix: None,
+            #[cfg(feature = "std")]
+            args: Vec::new(),
+            empty_string: "".to_string(),
+            updated: false,
+            secrets,
+            audited: KeySet::new(),
+            locked: KeySet::new(),
+            required: KeySet::new(),
+            found: KeySet::new(),
+            descriptions: BTreeMap::new(),
+            comments: BTreeMap::new(),
+            pending_comment: String::new(),
+            interner: None,
+            history: None,
+            origin: Arc::new(provenance::Origin::other("")),
+            origins: BTreeMap::new(),
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            snapshot: None,
+            #[cfg(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            preloaded: BTreeMap::new(),
+            limits: None,
+            validators: Default::default(),
+            subscribers: Default::default(),
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            expiry: Default::default(),
+            audit_hook: None,
+            aliases: BTreeMap::new(),
+            deprecation_hook: None,
+            metrics: None,
+            #[cfg(feature = "std")]
+            misses: Default::default(),
+            source: None,
+            #[cfg(feature = "encryption")]
+            decryption_key: None,
+            #[cfg(feature = "signing")]
+            public_key: None,
+            #[cfg(feature = "mmap")]
+            mmap: false,
+        }
+    }
+}
+
+/// Copies the values and the settings, sharing hooks, validators, subscribers and metrics with the original,
+/// e.g. to keep a snapshot from before an update. A source set with with_source can't be copied, so the
+/// clone has none. Neither does it copy what an update keeps while it runs, such as the keys seen so far, so
+/// the clone's next update starts afresh
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::Conf;
+/// let mut conf = Conf::from_defaults([("port", "8080")]);
+/// let before = conf.clone();
+/// conf.update_from_str("port: 9090").unwrap();
+/// assert_eq!(before["port"], "8080");
+/// assert_ne!(before, conf);
+/// ```
+impl Clone for Conf {
+    fn clone(&self) -> Self {
+        Self {
+            pairs: self.pairs.clone(),
+            defaults: self.defaults.clone(),
+            delims: self.delims.clone(),
+            comment_chars: self.comment_chars.clone(),
+            inline_comments: self.inline_comments,
+            quotes: self.quotes,
+            continuations: self.continuations,
+            list_separator: self.list_separator,
+            strict: self.strict,
+            interpolation: self.interpolation,
+            templates: self.templates.clone(),
+            old_values: BTreeMap::new(),
+            rollback: Default::default(),
+            sections: self.sections,
+            section: String::new(),
+            case_insensitive: self.case_insensitive,
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            includes: self.includes,
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            including: Vec::new(),
+            duplicates: self.duplicates,
+            seen: KeySet::new(),
+            profile: self.profile.clone(),
+            profiled: KeySet::new(),
+            format: self.format,
+            conf_file_name: self.conf_file_name.clone(),
+            #[cfg(feature = "std")]
+            candidates: self.candidates.clone(),
+            #[cfg(feature = "std")]
+            layer_candidates: self.layer_candidates,
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            conf_dir: self.conf_dir.clone(),
+            #[cfg(feature = "std")]
+            loaded_files: self.loaded_files.clone(),
+            #[cfg(feature = "std")]
+            env_prefix: self.env_prefix.clone(),
+            #[cfg(feature = "std")]
+            args: self.args.clone(),
+            empty_string: self.empty_string.clone(),
+            updated: self.updated,
+            secrets: self.secrets.clone(),
+            audited: self.audited.clone(),
+            locked: self.locked.clone(),
+            required: self.required.clone(),
+            found: self.found.clone(),
+            descriptions: self.descriptions.clone(),
+            comments: self.comments.clone(),
+            pending_comment: String::new(),
+            interner: self.interner.clone(),
+            history: self.history.clone(),
+            origin: Arc::clone(&self.origin),
+            origins: self.origins.clone(),
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            snapshot: self.snapshot.clone(),
+            #[cfg(all(feature = "async", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            preloaded: BTreeMap::new(),
+            limits: self.limits,
+            validators: self.validators.clone(),
+            subscribers: self.subscribers.clone(),
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            expiry: self.expiry.clone(),
+            audit_hook: self.audit_hook.clone(),
+            aliases: self.aliases.clone(),
+            deprecation_hook: self.deprecation_hook.clone(),
+            metrics: self.metrics.clone(),
+            #[cfg(feature = "std")]
+            misses: self.misses.clone(),
+            source: None,
+            #[cfg(feature = "encryption")]
+            decryption_key: self.decryption_key.clone(),
+            #[cfg(feature = "signing")]
+            public_key: self.public_key,
+            #[cfg(feature = "mmap")]
+            mmap: self.mmap,
+        }
+    }
+}
+
+/// Confs are equal when they hold the same keys with the same values, whatever their defaults and settings
+impl PartialEq for Conf {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs.len() == other.pairs.len()
+            && self.pairs.iter().all(|(key, value)| other.pairs.get(key).is_some_and(|other| other == value))
+    }
+}
+
+impl Eq for Conf {}
+
+/// Creates a Conf without any keys
+impl Default for Conf {
+    fn default() -> Self {
+        Self::from_iter(core::iter::empty())
+    }
+}
+
+/// Allows for the use of [ ]. Occasionally useful
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::Conf;
+/// let mut conf = Conf::from([
+///     ("foo".to_string(), "bar".to_string()),
+///     ("yee".to_string(), "haw".to_string()),
+/// ]);
+/// println!("{}", conf["foo"]);
+/// ```
+impl Index<&str> for Conf {
+    type Output = String;
+
+    #[track_caller]
+    fn index(&self, key: &str) -> &Self::Output {
+        self.lookup(key, Location::caller()).map(|v| &**v).unwrap_or(&self.empty_string)
+    }
+}
+
+impl<'a> IntoIterator for &'a Conf {
+    type Item = (&'a str, &'a str);
+    type IntoIter = Iter<'a>;
+
+    #[track_caller]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Consumes Conf into its keys and values, in no particular order
+impl IntoIterator for Conf {
+    type Item = (String, String);
+    type IntoIter = alloc::vec::IntoIter<(String, String)>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let pairs: Vec<(String, String)> = self
+            .pairs
+            .iter_mut()
+            .map(|(key, value)| {
+                let value = core::mem::take(value);
+                (key.clone(), Arc::try_unwrap(value).unwrap_or_else(|shared| shared.as_str().to_string()))
+            })
+            .collect();
+        pairs.into_iter()
+    }
+}
+
+/// Shows the values, masking those of secret keys, along with the settings that decide how updates read them
+impl Debug for Conf {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        type Pairs = store::Store<String, Arc<String>>;
+        fn masked<'a>(pairs: &'a Pairs, secrets: &KeySet) -> BTreeMap<&'a str, &'a str> {
+            pairs
+                .iter()
+                .map(|(key, value)| (key.as_str(), if secrets.contains(key) { REDACTED } else { value }))
+                .collect()
+        }
+        let mut secrets: Vec<&String> = self.secrets.iter().collect();
+        secrets.sort();
+        f.debug_struct("Conf")
+            .field("pairs", &masked(&self.pairs, &self.secrets))
+            .field("defaults", &masked(&self.defaults, &self.secrets))
+            .field("conf_file_name", &self.conf_file_name)
+            .field("delims", &self.delims)
+            .field("format", &self.format)
+            .field("strict", &self.strict)
+            .field("interpolation", &self.interpolation)
+            .field("updated", &self.updated)
+            .field("secrets", &secrets)
+            .field("limits", &self.limits)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Displays the config file as confee would expect to read it, sorted by key, with the values of secret keys
+/// masked
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::Conf;
+/// let mut conf = Conf::from([
+///     ("yee".to_string(), "haw".to_string()),
+///     ("foo".to_string(), "bar".to_string()),
 /// ]);
 /// println!("{}", conf);
+/// assert_eq!(conf.to_string(), "foo: bar\nyee: haw\n");
 /// ```
 impl Display for Conf {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        for (key, value) in &self.pairs {
-            let formatted_value = if value.is_empty() {
+        for (key, value) in self.pairs.sorted() {
+            let formatted_value = if self.is_secret(key) {
+                REDACTED
+            } else if value.is_empty() {
                 &self.empty_string
             } else {
                 value
             };
-            writeln!(f, "{}{} {}", key, self.delim(), formatted_value)?;
+            writeln!(f, "{}{} {}", key, self.delims, formatted_value)?;
         }
         Ok(())
     }
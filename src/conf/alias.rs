@@ -0,0 +1,25 @@
+use alloc::sync::Arc;
+use core::fmt::{self, Debug, Formatter};
+
+type Callback = dyn Fn(&str, &str) + Send + Sync;
+
+/// Callback invoked when an update sets a key through one of its aliases, see Conf::alias. Clones share
+/// the callback
+#[derive(Clone)]
+pub(crate) struct DeprecationHook(Arc<Callback>);
+
+impl DeprecationHook {
+    pub(crate) fn new(hook: impl Fn(&str, &str) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub(crate) fn call(&self, alias: &str, key: &str) {
+        (self.0)(alias, key);
+    }
+}
+
+impl Debug for DeprecationHook {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("DeprecationHook")
+    }
+}
@@ -0,0 +1,42 @@
+use alloc::sync::Arc;
+use core::fmt::{self, Debug, Formatter};
+use core::panic::Location;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+/// Describes a single read of an audited key
+#[derive(Debug, Clone, Copy)]
+pub struct AccessEvent<'a> {
+    /// The key that was read
+    pub key: &'a str,
+    /// Where in the application's source the read happened
+    pub location: &'static Location<'static>,
+    /// When the read happened, only known with the `std` feature
+    #[cfg(feature = "std")]
+    pub time: SystemTime,
+}
+
+/// Callback invoked on every read of an audited key. Clones share the callback
+#[derive(Clone)]
+pub(crate) struct AuditHook(Arc<dyn Fn(&AccessEvent) + Send + Sync>);
+
+impl AuditHook {
+    pub(crate) fn new(hook: impl Fn(&AccessEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    pub(crate) fn call(&self, key: &str, location: &'static Location<'static>) {
+        (self.0)(&AccessEvent {
+            key,
+            location,
+            #[cfg(feature = "std")]
+            time: SystemTime::now(),
+        });
+    }
+}
+
+impl Debug for AuditHook {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("AuditHook")
+    }
+}
@@ -0,0 +1,96 @@
+use super::store::Store;
+use super::{split_line, ConfError, DEFAULT_COMMENT_CHARS, DEFAULT_DELIM};
+use core::ops::Index;
+use core::str::FromStr;
+
+/// ConfRef is the zero-copy counterpart of Conf. Defaults, keys and values are all borrowed as slices of
+/// data owned by the caller, so parsing a buffer never allocates beyond the map itself. It is meant for hot
+/// start-up paths, and leaves out the features that need to own their values, such as decryption.
+///
+
+#[derive(Debug, Clone)]
+pub struct ConfRef<'a> {
+    pairs: Store<&'a str, &'a str>,
+    delim: Option<char>,
+    updated: bool,
+}
+
+impl<'a> ConfRef<'a> {
+    /// Creates a ConfRef, given user defaults
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::ConfRef;
+    /// let mut conf = ConfRef::from([("foo", "bar"), ("yee", "haw")]);
+    /// ```
+    pub fn from<const N: usize>(defaults: [(&'a str, &'a str); N]) -> Self {
+        Self {
+            pairs: defaults.into_iter().collect(),
+            delim: None,
+            updated: false,
+        }
+    }
+
+    /// Sets the delimiter for this ConfRef
+    pub fn with_delim(&mut self, delim: char) -> &mut Self {
+        self.delim = Some(delim);
+        self
+    }
+    pub fn and_delim(&mut self, delim: char) -> &mut Self {
+        self.with_delim(delim)
+    }
+    /// Gets the delimiter set for this ConfRef
+    pub fn delim(&self) -> char {
+        self.delim.unwrap_or(DEFAULT_DELIM)
+    }
+
+    /// Updates ConfRef with the values found in input, which must outlive it
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::ConfRef;
+    /// let input = "foo: baz\nyee: hoo\n";
+    /// let mut conf = ConfRef::from([("foo", "bar"), ("yee", "haw")]);
+    /// conf.update_from(input).unwrap();
+    /// assert_eq!(&conf["foo"], "baz");
+    /// ```
+    pub fn update_from(&mut self, input: &'a str) -> Result<(), ConfError> {
+        let delims = self.delim().into();
+        for (i, line) in input.lines().enumerate() {
+            let Some((key, value)) = split_line(i + 1, line, &delims, DEFAULT_COMMENT_CHARS)? else {
+                continue;
+            };
+            if let Some(v) = self.pairs.get_mut(key) {
+                *v = value;
+            }
+        }
+        self.updated = true;
+        Ok(())
+    }
+
+    /// Gets the update status for this ConfRef
+    pub fn is_updated(&self) -> bool {
+        self.updated
+    }
+
+    /// Gets the raw value of key, borrowed from the defaults or the parsed input
+    pub fn get_str(&self, key: &str) -> Option<&'a str> {
+        self.pairs.get(key).copied()
+    }
+
+    /// Function to index into ConfRef, and attempt type conversion
+    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get_str(key).and_then(|v| v.parse::<T>().ok())
+    }
+}
+
+/// Allows for the use of [ ], like Conf does
+impl Index<&str> for ConfRef<'_> {
+    type Output = str;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        self.get_str(key).unwrap_or("")
+    }
+}
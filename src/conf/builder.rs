@@ -0,0 +1,128 @@
+use super::{AccessEvent, Conf, ConfError, ConfMetrics, DuplicatePolicy, Format, Limits, Source, Validator};
+use alloc::string::String;
+
+/// ConfBuilder sets up a Conf by value rather than through `&mut`, so a Conf can be set up and loaded in a
+/// single expression. Its methods mirror those of Conf with the same names
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::Conf;
+/// let conf = Conf::builder([("port", "8080"), ("log", "stdout")])
+///     .with_delim('=')
+///     .with_str("port = 9090")
+///     .load()?;
+/// assert_eq!(conf["port"], "9090");
+/// # Ok::<(), confee::conf::ConfError>(())
+/// ```
+#[derive(Debug)]
+pub struct ConfBuilder {
+    conf: Conf,
+}
+
+// Forwards each method to the Conf being built, taking and giving back the builder
+macro_rules! forward {
+    ($($(#[$attr:meta])* fn $name:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            $(#[$attr])*
+            #[doc = concat!("See Conf::", stringify!($name))]
+            pub fn $name(mut self, $($arg: $ty),*) -> Self {
+                self.conf.$name($($arg),*);
+                self
+            }
+        )*
+    };
+}
+
+impl ConfBuilder {
+    /// Starts building a Conf with the given defaults, see Conf::from_defaults
+    pub fn new<I, K, V>(defaults: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Conf::from_defaults(defaults).into()
+    }
+
+    /// Gives the Conf as set up, holding its defaults until updated
+    pub fn build(self) -> Conf {
+        self.conf
+    }
+
+    /// Gives the Conf as set up, once updated from its file or source
+    pub fn load(self) -> Result<Conf, ConfError> {
+        let mut conf = self.conf;
+        conf.update()?;
+        Ok(conf)
+    }
+
+    forward! {
+        fn with_delim(delim: char);
+        fn with_delims(delims: &[char]);
+        fn with_delim_str(delim: &str);
+        fn with_comment_chars(chars: &[char]);
+        fn with_inline_comments(inline_comments: bool);
+        fn with_quotes(quotes: bool);
+        fn with_continuations(continuations: bool);
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        fn with_includes(includes: bool);
+        fn with_list_separator(separator: char);
+        fn with_interpolation(interpolation: bool);
+        fn with_strict(strict: bool);
+        fn with_duplicates(policy: DuplicatePolicy);
+        fn with_case_insensitive(case_insensitive: bool);
+        fn with_sections(sections: bool);
+        fn with_format(format: Format);
+        fn with_file(conf_file_name: &str);
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        fn with_snapshot(path: &str);
+        #[cfg(feature = "std")]
+        fn with_files(files: impl IntoIterator<Item = impl AsRef<str>>);
+        #[cfg(feature = "std")]
+        fn with_layered_files(layered: bool);
+        #[cfg(feature = "std")]
+        fn with_layers(files: impl IntoIterator<Item = impl AsRef<str>>);
+        #[cfg(feature = "std")]
+        fn discover(app: &str);
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        fn with_dir(dir: &str);
+        #[cfg(feature = "std")]
+        fn with_env_prefix(prefix: &str);
+        #[cfg(feature = "std")]
+        fn with_args(args: impl IntoIterator<Item = impl AsRef<str>>);
+        #[cfg(feature = "encryption")]
+        fn with_decryption_key(key: [u8; 32]);
+        #[cfg(feature = "signing")]
+        fn with_public_key(public_key: [u8; 32]);
+        #[cfg(feature = "mmap")]
+        fn with_mmap(mmap: bool);
+        fn with_profile(profile: &str);
+        fn with_source(source: impl Source + Send + Sync + 'static);
+        #[cfg(feature = "std")]
+        fn with_reader(reader: impl std::io::Read + Send + Sync + 'static);
+        fn with_str(contents: &str);
+        fn with_metrics(metrics: impl ConfMetrics + 'static);
+        fn with_limits(limits: Limits);
+        fn with_capacity(capacity: usize);
+        fn with_interning(interning: bool);
+        fn with_history(limit: usize);
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        fn with_ttl(key: &str, ttl: std::time::Duration);
+        fn with_rule(key: &str, validator: impl Validator + 'static);
+        fn with_audit_hook(hook: impl Fn(&AccessEvent) + Send + Sync + 'static);
+        fn with_deprecation_hook(hook: impl Fn(&str, &str) + Send + Sync + 'static);
+        fn mark_secret(key: &str);
+        fn lock(key: &str);
+        fn alias(alias: &str, key: &str);
+        fn describe(key: &str, description: &str);
+        fn require(key: &str);
+        fn on_change(key: &str, callback: impl Fn(&str, &str) + Send + Sync + 'static);
+    }
+}
+
+impl From<Conf> for ConfBuilder {
+    fn from(conf: Conf) -> Self {
+        Self { conf }
+    }
+}
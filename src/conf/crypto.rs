@@ -0,0 +1,92 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::borrow::Cow;
+use std::env;
+use std::fmt::{self, Debug, Formatter};
+
+const ENC_PREFIX: &str = "enc:";
+const KEY_ENV_VAR: &str = "CONFEE_DECRYPTION_KEY";
+const NONCE_LEN: usize = 12;
+
+/// AES-256 key used to decrypt values. Never printed, so dbg!(conf) doesn't leak it
+#[derive(Clone)]
+pub(crate) struct DecryptionKey([u8; 32]);
+
+impl DecryptionKey {
+    pub(crate) fn new(key: [u8; 32]) -> Self {
+        Self(key)
+    }
+
+    fn from_env() -> Result<Self, String> {
+        let encoded = env::var(KEY_ENV_VAR)
+            .map_err(|_| format!("No decryption key set, and {} is not set", KEY_ENV_VAR))?;
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| format!("Invalid base64 in {}: {}", KEY_ENV_VAR, e))?;
+        let key = bytes
+            .try_into()
+            .map_err(|_| format!("{} must decode to exactly 32 bytes", KEY_ENV_VAR))?;
+        Ok(Self(key))
+    }
+}
+
+impl Debug for DecryptionKey {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("DecryptionKey(****)")
+    }
+}
+
+/// Encrypts a value with AES-256-GCM, producing the `enc:<base64>` form that confee decrypts during update
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::encrypt;
+/// let key = [7u8; 32];
+/// let value = encrypt(&key, "hunter2");
+/// assert!(value.starts_with("enc:"));
+/// ```
+pub fn encrypt(key: &[u8; 32], plaintext: &str) -> String {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .expect("AES-GCM encryption of an in-memory buffer cannot fail");
+    let mut payload = nonce.to_vec();
+    payload.extend(ciphertext);
+    format!("{}{}", ENC_PREFIX, STANDARD.encode(payload))
+}
+
+/// Decrypts value if it carries the `enc:` prefix, otherwise hands it back untouched
+pub(crate) fn decrypt_value<'a>(
+    key: Option<&DecryptionKey>,
+    name: &str,
+    value: &'a str,
+) -> Result<Cow<'a, str>, String> {
+    let Some(encoded) = value.strip_prefix(ENC_PREFIX) else {
+        return Ok(Cow::Borrowed(value));
+    };
+    let env_key;
+    let key = match key {
+        Some(key) => key,
+        None => {
+            env_key = DecryptionKey::from_env()?;
+            &env_key
+        }
+    };
+    let failed = || format!("Failed to decrypt value for key: {}", name);
+    let payload = STANDARD.decode(encoded).map_err(|_| failed())?;
+    if payload.len() < NONCE_LEN {
+        return Err(failed());
+    }
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key.0));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| failed())?;
+    String::from_utf8(plaintext)
+        .map(Cow::Owned)
+        .map_err(|_| failed())
+}
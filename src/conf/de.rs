@@ -0,0 +1,335 @@
+use super::{parse_bool, split_list, Conf, ConfError, REDACTED};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Display;
+use core::panic::Location;
+use core::str::FromStr;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::forward_to_deserialize_any;
+
+impl de::Error for ConfError {
+    fn custom<T: Display>(msg: T) -> Self {
+        ConfError::Other(msg.to_string())
+    }
+}
+
+/// Deserializes a map of keys and string values, as Serialize writes them, into a Conf with those values as
+/// its defaults
+impl<'de> de::Deserialize<'de> for Conf {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <BTreeMap<String, String> as de::Deserialize>::deserialize(deserializer).map(Conf::from_iter)
+    }
+}
+
+/// Deserializes the keys under a prefix, e.g. `server` for `server.port` and `server.addr`, as a map, a
+/// struct, or as a sequence when they are numbered
+pub(crate) struct Node<'a> {
+    conf: &'a Conf,
+    prefix: String,
+    location: &'static Location<'static>,
+}
+
+impl<'a> Node<'a> {
+    pub(crate) fn root(conf: &'a Conf, location: &'static Location<'static>) -> Self {
+        Self {
+            conf,
+            prefix: String::new(),
+            location,
+        }
+    }
+
+    fn key(&self, name: &str) -> String {
+        if self.prefix.is_empty() {
+            name.to_string()
+        } else {
+            alloc::format!("{}.{}", self.prefix, name)
+        }
+    }
+
+    // The first segment of every key under the prefix, sorted and without duplicates
+    fn children(&self) -> BTreeSet<&'a str> {
+        let conf: &'a Conf = self.conf;
+        conf.pairs
+            .iter()
+            .filter_map(|(key, _)| {
+                let rest = if self.prefix.is_empty() {
+                    key.as_str()
+                } else {
+                    key.strip_prefix(self.prefix.as_str())?.strip_prefix('.')?
+                };
+                rest.split('.').next()
+            })
+            .collect()
+    }
+
+    // A key holding a value is read as that value, anything else as the keys nested under it
+    fn child(&self, name: &str) -> Child<'a> {
+        let key = self.key(name);
+        match self.conf.pairs.get(&key) {
+            Some(value) => {
+                self.conf.audit(&key, self.location);
+                Child::Value(Value {
+                    secret: self.conf.is_secret(&key),
+                    separator: self.conf.list_separator,
+                    key,
+                    value: value.as_str(),
+                })
+            }
+            None => Child::Node(Node {
+                conf: self.conf,
+                prefix: key,
+                location: self.location,
+            }),
+        }
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Node<'a> {
+    type Error = ConfError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConfError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConfError> {
+        let children = self.children().into_iter().collect();
+        visitor.visit_map(Children {
+            node: self,
+            children,
+            next: 0,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ConfError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConfError> {
+        let mut children: Vec<(usize, &str)> = self
+            .children()
+            .into_iter()
+            .filter_map(|name| Some((name.parse().ok()?, name)))
+            .collect();
+        children.sort();
+        visitor.visit_seq(Children {
+            node: self,
+            children: children.into_iter().map(|(_, name)| name).collect(),
+            next: 0,
+        })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConfError> {
+        if self.children().is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ConfError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct tuple tuple_struct enum identifier ignored_any
+    }
+}
+
+/// What a name under a Node stands for
+enum Child<'a> {
+    Value(Value<'a>),
+    Node(Node<'a>),
+}
+
+/// Walks the children of a Node, as map entries or as sequence elements
+struct Children<'a> {
+    node: Node<'a>,
+    children: Vec<&'a str>,
+    next: usize,
+}
+
+impl<'de, 'a> MapAccess<'de> for Children<'a> {
+    type Error = ConfError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, ConfError> {
+        let Some(name) = self.children.get(self.next) else {
+            return Ok(None);
+        };
+        seed.deserialize((*name).into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, ConfError> {
+        let name = self.children[self.next];
+        self.next += 1;
+        match self.node.child(name) {
+            Child::Value(value) => seed.deserialize(value),
+            Child::Node(node) => seed.deserialize(node),
+        }
+    }
+}
+
+impl<'de, 'a> SeqAccess<'de> for Children<'a> {
+    type Error = ConfError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, ConfError> {
+        let Some(name) = self.children.get(self.next) else {
+            return Ok(None);
+        };
+        self.next += 1;
+        match self.node.child(name) {
+            Child::Value(value) => seed.deserialize(value).map(Some),
+            Child::Node(node) => seed.deserialize(node).map(Some),
+        }
+    }
+}
+
+/// Deserializes a single value, parsing it into whatever type is asked for
+struct Value<'a> {
+    key: String,
+    value: &'a str,
+    secret: bool,
+    separator: char,
+}
+
+impl Value<'_> {
+    fn parse<T>(&self) -> Result<T, ConfError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.value.parse().map_err(|e: T::Err| ConfError::Parse {
+            key: self.key.clone(),
+            value: if self.secret { REDACTED.to_string() } else { self.value.to_string() },
+            message: e.to_string(),
+            line_no: None,
+        })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConfError> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Value<'_> {
+    type Error = ConfError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConfError> {
+        visitor.visit_str(self.value)
+    }
+
+    // Accepts the spellings get_bool does
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConfError> {
+        match parse_bool(self.value) {
+            Some(value) => visitor.visit_bool(value),
+            None => Err(ConfError::Parse {
+                key: self.key.clone(),
+                value: if self.secret { REDACTED.to_string() } else { self.value.to_string() },
+                message: "Expected true, yes, on, false, no or off".to_string(),
+                line_no: None,
+            }),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    // An empty value stands for None
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConfError> {
+        if self.value.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    // A list written on one line, split as get_vec does
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConfError> {
+        let items: Vec<&str> = split_list(self.value, self.separator).collect();
+        visitor.visit_seq(Items {
+            key: &self.key,
+            secret: self.secret,
+            separator: self.separator,
+            items: items.into_iter(),
+        })
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ConfError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ConfError> {
+        visitor.visit_enum(self.value.into_deserializer())
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ConfError> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// The items of a list value
+struct Items<'k, 'a> {
+    key: &'k str,
+    secret: bool,
+    separator: char,
+    items: alloc::vec::IntoIter<&'a str>,
+}
+
+impl<'de> SeqAccess<'de> for Items<'_, '_> {
+    type Error = ConfError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, ConfError> {
+        let Some(item) = self.items.next() else {
+            return Ok(None);
+        };
+        seed.deserialize(Value {
+            key: self.key.to_string(),
+            value: item,
+            secret: self.secret,
+            separator: self.separator,
+        })
+        .map(Some)
+    }
+}
@@ -0,0 +1,56 @@
+use super::DEFAULT_DELIM;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+/// The delimiters a Conf accepts between keys and values, see Conf::with_delims. The first one is the
+/// one written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Delims(Vec<String>);
+
+impl Delims {
+    /// Empty delimiters are left out, and without any the default one applies
+    pub(crate) fn new(delims: impl IntoIterator<Item = String>) -> Self {
+        let delims: Vec<String> = delims.into_iter().filter(|delim| !delim.is_empty()).collect();
+        if delims.is_empty() {
+            return Self::default();
+        }
+        Self(delims)
+    }
+
+    /// The delimiter written between keys and values
+    pub(crate) fn written(&self) -> &str {
+        &self.0[0]
+    }
+
+    /// Finds the first delimiter in line, giving its start and length. Where several start at the same
+    /// place, the longest wins, so `=>` is found whole even when `=` is accepted too
+    pub(crate) fn find(&self, line: &str) -> Option<(usize, usize)> {
+        if let [delim] = self.0.as_slice() {
+            return line.find(delim.as_str()).map(|i| (i, delim.len()));
+        }
+        line.char_indices().find_map(|(i, _)| {
+            let rest = &line[i..];
+            let longest = self.0.iter().filter(|delim| rest.starts_with(delim.as_str())).map(String::len).max();
+            longest.map(|len| (i, len))
+        })
+    }
+}
+
+impl Default for Delims {
+    fn default() -> Self {
+        DEFAULT_DELIM.into()
+    }
+}
+
+impl From<char> for Delims {
+    fn from(delim: char) -> Self {
+        Self(alloc::vec![delim.to_string()])
+    }
+}
+
+impl Display for Delims {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.written())
+    }
+}
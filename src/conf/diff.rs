@@ -0,0 +1,163 @@
+use super::{Conf, REDACTED};
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::slice;
+
+/// A key whose value differs between two configurations, e.g. before and after an update. Secret values
+/// are masked
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KeyChange {
+    /// The key that changed
+    pub key: String,
+    /// The value it held before, None when the key was added
+    pub old: Option<String>,
+    /// The value it holds now, None when the key was removed
+    pub new: Option<String>,
+}
+
+impl KeyChange {
+    /// Checks whether the key was added
+    pub fn is_added(&self) -> bool {
+        self.old.is_none()
+    }
+    /// Checks whether the key was removed
+    pub fn is_removed(&self) -> bool {
+        self.new.is_none()
+    }
+    /// Checks whether the key is held on both sides, with different values
+    pub fn is_modified(&self) -> bool {
+        self.old.is_some() && self.new.is_some()
+    }
+}
+
+impl Display for KeyChange {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "{}: {} -> {}", self.key, old, new),
+            (None, Some(new)) => write!(f, "{} added: {}", self.key, new),
+            (Some(old), None) => write!(f, "{} removed: {}", self.key, old),
+            (None, None) => write!(f, "{} unchanged", self.key),
+        }
+    }
+}
+
+/// The keys whose values differ between two configurations, sorted by key. Keys holding the same value on
+/// both sides are left out
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChangeSet(Vec<KeyChange>);
+
+impl ChangeSet {
+    /// Compares the values conf held before an update, as noted by the update, with those it holds now
+    pub(crate) fn since(old_values: BTreeMap<String, String>, conf: &Conf) -> Self {
+        let changes = old_values
+            .into_iter()
+            .filter_map(|(key, old)| {
+                let new = conf.pairs.get(&key)?;
+                // Secret values are noted masked, so a secret that changed back is still reported
+                let secret = conf.is_secret(&key);
+                let new = if secret { REDACTED.to_string() } else { new.to_string() };
+                (secret || new != old).then_some(KeyChange {
+                    key,
+                    old: Some(old),
+                    new: Some(new),
+                })
+            })
+            .collect();
+        Self(changes)
+    }
+
+    /// Compares the values of old with those of new
+    pub(crate) fn between(old: &Conf, new: &Conf) -> Self {
+        let mut keys: BTreeMap<&str, (Option<&str>, Option<&str>)> = BTreeMap::new();
+        for (key, value) in old.pairs.iter() {
+            keys.entry(key).or_default().0 = Some(value);
+        }
+        for (key, value) in new.pairs.iter() {
+            keys.entry(key).or_default().1 = Some(value);
+        }
+        let changes = keys
+            .into_iter()
+            .filter(|(_, (old_value, new_value))| old_value != new_value)
+            .map(|(key, (old_value, new_value))| {
+                let secret = old.is_secret(key) || new.is_secret(key);
+                let mask = |value: Option<&str>| value.map(|v| if secret { REDACTED } else { v }.to_string());
+                KeyChange {
+                    key: key.to_string(),
+                    old: mask(old_value),
+                    new: mask(new_value),
+                }
+            })
+            .collect();
+        Self(changes)
+    }
+
+    /// Checks whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Gets how many keys changed
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Checks whether key changed
+    pub fn contains(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Gets the change of key, if it changed
+    pub fn get(&self, key: &str) -> Option<&KeyChange> {
+        self.0.binary_search_by(|change| change.key.as_str().cmp(key)).ok().map(|i| &self.0[i])
+    }
+
+    /// Gets the changes, sorted by key
+    pub fn iter(&self) -> slice::Iter<'_, KeyChange> {
+        self.0.iter()
+    }
+
+    /// Gets the keys that were added
+    pub fn added(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().filter(|change| change.is_added()).map(|change| change.key.as_str())
+    }
+
+    /// Gets the keys that were removed
+    pub fn removed(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().filter(|change| change.is_removed()).map(|change| change.key.as_str())
+    }
+
+    /// Gets the keys whose value was modified
+    pub fn modified(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().filter(|change| change.is_modified()).map(|change| change.key.as_str())
+    }
+}
+
+impl<'a> IntoIterator for &'a ChangeSet {
+    type Item = &'a KeyChange;
+    type IntoIter = slice::Iter<'a, KeyChange>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for ChangeSet {
+    type Item = KeyChange;
+    type IntoIter = alloc::vec::IntoIter<KeyChange>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Notes the value key held before an update first changed it, masked if secret
+pub(crate) fn note(old_values: &mut BTreeMap<String, String>, key: &str, old: &str, secret: bool) {
+    if !old_values.contains_key(key) {
+        let old = if secret { REDACTED } else { old };
+        old_values.insert(key.to_string(), old.to_string());
+    }
+}
@@ -0,0 +1,23 @@
+/// What update does with a key set more than once by the same file, see Conf::with_duplicates. Later
+/// layers overriding earlier ones aren't duplicates
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::{Conf, DuplicatePolicy};
+/// let mut conf = Conf::from_defaults([("hosts", "")]);
+/// conf.with_duplicates(DuplicatePolicy::Append(','));
+/// conf.update_from_str("hosts: a.example.com\nhosts: b.example.com").unwrap();
+/// assert_eq!(conf["hosts"], "a.example.com,b.example.com");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// The update fails
+    Error,
+    /// The first value is kept and the others ignored
+    FirstWins,
+    /// Each value replaces the one before
+    LastWins,
+    /// The values are joined with the separator, in the order they come in, e.g. for get_vec
+    Append(char),
+}
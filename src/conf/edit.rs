@@ -0,0 +1,315 @@
+use super::delim::Delims;
+use super::lines::{continues, is_pair};
+use super::{quote, split_line, ConfError, DEFAULT_COMMENT_CHARS};
+use core::ops::Range;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Editor changes values in a confee file in place. Only the lines holding edited values are rewritten;
+/// every other byte of the file, including comments, blank lines, alignment and key order, is kept as it
+/// was.
+///
+
+#[derive(Debug, Clone)]
+pub struct Editor {
+    file_name: String,
+    delims: Delims,
+    comment_chars: Vec<char>,
+    inline_comments: bool,
+    sections: bool,
+    quotes: bool,
+    continuations: bool,
+    ignore_case: bool,
+    lines: Vec<Line>,
+}
+
+// A line of the file, as written, along with what it holds
+#[derive(Debug, Clone)]
+struct Line {
+    text: String,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    // Blank lines, comments and lines that don't parse, all kept untouched
+    Other,
+    // A `[section]` header, by name
+    Section(String),
+    // A pair: the key, nested under its section, and the span of its value in the text
+    Pair { key: String, value: Range<usize> },
+    // A line continuing the value of the pair above, see Conf::with_continuations
+    Continuation,
+}
+
+impl Editor {
+    /// Loads file_name for editing
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use confee::conf::Editor;
+    /// let mut editor = Editor::open("app.conf", ':').unwrap();
+    /// editor.set("port", "9090");
+    /// editor.save().unwrap();
+    /// ```
+    pub fn open(file_name: &str, delim: char) -> Result<Self, ConfError> {
+        Self::open_with(file_name, delim.into())
+    }
+    pub(crate) fn open_with(file_name: &str, delims: Delims) -> Result<Self, ConfError> {
+        let contents = fs::read_to_string(file_name).map_err(|source| ConfError::Io {
+            file: file_name.to_string(),
+            source,
+        })?;
+        Ok(Self::from_contents(file_name, &contents, delims))
+    }
+    pub(crate) fn from_contents(file_name: &str, contents: &str, delims: impl Into<Delims>) -> Self {
+        let mut editor = Self {
+            file_name: file_name.to_string(),
+            delims: delims.into(),
+            comment_chars: DEFAULT_COMMENT_CHARS.to_vec(),
+            inline_comments: false,
+            sections: false,
+            quotes: false,
+            continuations: false,
+            ignore_case: false,
+            lines: contents
+                .split_inclusive('\n')
+                .map(|text| Line {
+                    text: text.to_string(),
+                    kind: Kind::Other,
+                })
+                .collect(),
+        };
+        editor.parse();
+        editor
+    }
+    /// Reads the file the way a Conf with these settings does, see Conf::with_comment_chars,
+    /// Conf::with_inline_comments, Conf::with_sections, Conf::with_quotes and Conf::with_continuations
+    pub(crate) fn with_syntax(
+        mut self,
+        comment_chars: &[char],
+        inline: bool,
+        sections: bool,
+        quotes: bool,
+        continuations: bool,
+    ) -> Self {
+        self.comment_chars = comment_chars.to_vec();
+        self.inline_comments = inline;
+        self.sections = sections;
+        self.quotes = quotes;
+        self.continuations = continuations;
+        self.parse();
+        self
+    }
+    /// Matches keys whatever the case of their ASCII letters, see Conf::with_case_insensitive
+    pub(crate) fn with_ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Gets the contents of the file, as they would be saved
+    pub fn contents(&self) -> String {
+        self.lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    /// Gets the value of key, from the last line defining it
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let line = self.lines.iter().rev().find(|line| line.defines(key, self.ignore_case))?;
+        match &line.kind {
+            Kind::Pair { value, .. } => Some(&line.text[value.clone()]),
+            _ => None,
+        }
+    }
+
+    /// Sets the value of key. The last line defining key is rewritten, as that is the one update applies;
+    /// when no line defines it, a new one is added, under the section the key is nested in if the file
+    /// has one. Any lines continuing the old value are removed
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        match self.lines.iter().rposition(|line| line.defines(key, self.ignore_case)) {
+            Some(i) => {
+                self.lines[i].set(value);
+                let continued = self.lines[i + 1..].iter().take_while(|line| line.kind == Kind::Continuation);
+                let end = i + 1 + continued.count();
+                self.lines.drain(i + 1..end);
+            }
+            None => self.insert(key, value),
+        }
+        self
+    }
+
+    /// Removes every line defining key, along with the lines continuing its value
+    pub fn remove(&mut self, key: &str) -> &mut Self {
+        let mut removed = false;
+        self.lines.retain(|line| {
+            removed = line.defines(key, self.ignore_case) || removed && line.kind == Kind::Continuation;
+            !removed
+        });
+        self
+    }
+
+    // Adds a line for key. Without sections it is appended. With them it goes after the last pair of the
+    // deepest section key is nested in, or else after the last pair outside any section, where the full key
+    // reads the same
+    fn insert(&mut self, key: &str, value: &str) {
+        let mut at = self.lines.len();
+        let mut key = key;
+        if self.sections {
+            let (mut section, mut in_section, mut top_level) = (0, None, None);
+            // Whether the lines are at the top level, and whether they are in the deepest section yet
+            let (mut at_top, mut in_deepest) = (true, false);
+            for (i, line) in self.lines.iter().enumerate() {
+                match &line.kind {
+                    Kind::Section(name) => {
+                        at_top = name.is_empty();
+                        in_deepest = !at_top && nested_in(key, name) && name.len() >= section;
+                        if at_top {
+                            top_level = Some(i + 1);
+                        } else if in_deepest {
+                            section = name.len();
+                            in_section = Some(i + 1);
+                        }
+                    }
+                    Kind::Pair { .. } | Kind::Continuation if at_top => top_level = Some(i + 1),
+                    Kind::Pair { .. } | Kind::Continuation if in_deepest => in_section = Some(i + 1),
+                    _ => {}
+                }
+            }
+            let first_section = self.lines.iter().position(|line| matches!(line.kind, Kind::Section(_)));
+            match in_section {
+                Some(i) => (at, key) = (i, &key[section + 1..]),
+                None => at = top_level.or(first_section).unwrap_or(at),
+            }
+        }
+        if let Some(previous) = at.checked_sub(1).map(|i| &mut self.lines[i]) {
+            if !previous.text.ends_with('\n') {
+                previous.text.push('\n');
+            }
+        }
+        let line = Line {
+            text: format!("{}{} {}\n", key, self.delims, value),
+            kind: Kind::Other,
+        };
+        self.lines.insert(at, line);
+        self.parse();
+    }
+
+    // Works out what each line holds, tracking the section it is in and, with continuations, whether the
+    // line above leaves the value of a pair open
+    fn parse(&mut self) {
+        let mut section = String::new();
+        let (mut in_pair, mut joined) = (false, false);
+        let mut lines = core::mem::take(&mut self.lines);
+        for line in &mut lines {
+            let text = line.text.trim_end_matches(['\r', '\n']);
+            line.kind = if in_pair && (joined || continues(text, &self.comment_chars)) {
+                Kind::Continuation
+            } else {
+                self.kind(&line.text, &section)
+            };
+            if let Kind::Section(name) = &line.kind {
+                section.clone_from(name);
+            }
+            in_pair = self.continuations
+                && (line.kind == Kind::Continuation || is_pair(text, &self.delims, &self.comment_chars));
+            joined = text.trim_end().ends_with('\\');
+        }
+        self.lines = lines;
+    }
+
+    fn kind(&self, text: &str, section: &str) -> Kind {
+        if self.sections {
+            if let Some(name) = text.trim().strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                return Kind::Section(name.trim().to_string());
+            }
+        }
+        let Ok(Some((key, value))) = split_line(0, text, &self.delims, &self.comment_chars) else {
+            return Kind::Other;
+        };
+        let written = quote::written(value, self.quotes, self.inline_comments, &self.comment_chars);
+        let value = written.unwrap_or(value);
+        // The value is a slice of text, so its offset gives its span. An empty value sits right after the
+        // delimiter, with a space put in front of whatever replaces it
+        let start = if value.is_empty() {
+            self.delims.find(text).map_or(0, |(i, len)| i + len)
+        } else {
+            value.as_ptr() as usize - text.as_ptr() as usize
+        };
+        let key = if section.is_empty() { key.to_string() } else { format!("{}.{}", section, key) };
+        Kind::Pair {
+            key,
+            value: start..start + value.len(),
+        }
+    }
+
+    /// Writes the edited contents back to the file. The new contents go to a temporary file next to it that
+    /// then replaces the original, so readers never observe a half-written file. The temporary file takes
+    /// the permissions of the original and is flushed to disk before the replacement, and each save uses a
+    /// name of its own, so that concurrent saves don't write over each other's
+    pub fn save(&self) -> Result<(), ConfError> {
+        let path = Path::new(&self.file_name);
+        let io_error = |source| ConfError::Io {
+            file: self.file_name.clone(),
+            source,
+        };
+        let permissions = fs::metadata(path).map(|metadata| metadata.permissions()).ok();
+        let (tmp_path, mut file) = temp_file(path).map_err(io_error)?;
+        let written = file
+            .write_all(self.contents().as_bytes())
+            .and_then(|()| permissions.map_or(Ok(()), |permissions| file.set_permissions(permissions)))
+            .and_then(|()| file.sync_all())
+            .and_then(|()| fs::rename(&tmp_path, path));
+        if written.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        written.map_err(io_error)
+    }
+}
+
+// Creates a file next to path, named after it, that no other save is writing to. Names already taken, be it
+// by another process or by a save that was interrupted, are skipped
+fn temp_file(path: &Path) -> io::Result<(PathBuf, File)> {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    loop {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let tmp_path = path.with_file_name(format!("{}.{}.tmp", name, id));
+        match OpenOptions::new().write(true).create_new(true).open(&tmp_path) {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            opened => return opened.map(|file| (tmp_path, file)),
+        }
+    }
+}
+
+impl Line {
+    fn defines(&self, key: &str, ignore_case: bool) -> bool {
+        match &self.kind {
+            Kind::Pair { key: k, .. } => k == key || ignore_case && k.eq_ignore_ascii_case(key),
+            _ => false,
+        }
+    }
+
+    // Replaces the value, keeping everything around it: the key, the delimiter, the padding, any inline
+    // comment and the line ending
+    fn set(&mut self, value: &str) {
+        let Kind::Pair { value: span, .. } = &mut self.kind else {
+            return;
+        };
+        if span.start == span.end {
+            // Nothing to keep after an empty value but the line ending
+            let end = self.text.trim_end_matches(['\r', '\n']).len();
+            self.text.replace_range(span.start..end, &format!(" {}", value));
+            *span = span.start + 1..span.start + 1 + value.len();
+        } else {
+            self.text.replace_range(span.clone(), value);
+            *span = span.start..span.start + value.len();
+        }
+    }
+}
+
+// Whether key is nested in section, i.e. starts with the section's name and a dot
+fn nested_in(key: &str, section: &str) -> bool {
+    key.strip_prefix(section).is_some_and(|rest| rest.starts_with('.'))
+}
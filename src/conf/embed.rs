@@ -0,0 +1,162 @@
+// Support for the conf! and conf_embed! macros. The compile-time parsing behind conf_embed! mirrors
+// split_line, on byte slices so it can run in const fns
+
+use alloc::string::{String, ToString};
+use core::fmt::Display;
+
+/// Builds the defaults of a Conf, sparing the `.to_string()` on every key and value. Keys are identifiers,
+/// dotted identifiers or string literals; values are anything Display, such as string or number literals
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::Conf;
+/// use confee::conf;
+///
+/// let conf = Conf::from(conf! {
+///     log: "stdout",
+///     port: 8080,
+///     server.tls: false,
+///     "max-connections": 512,
+/// });
+/// assert_eq!(conf["port"], "8080");
+/// assert_eq!(conf["server.tls"], "false");
+/// assert_eq!(conf["max-connections"], "512");
+/// ```
+#[macro_export]
+macro_rules! conf {
+    (@pairs [$($pairs:expr,)*]) => {
+        [$($pairs),*]
+    };
+    (@pairs [$($pairs:expr,)*] $key:literal : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::conf!(@pairs [$($pairs,)* $crate::conf::embed::key_value($key, $value),] $($($rest)*)?)
+    };
+    (@pairs [$($pairs:expr,)*] $($part:ident).+ : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::conf!(
+            @pairs [$($pairs,)* $crate::conf::embed::path_value(&[$(stringify!($part)),+], $value),]
+            $($($rest)*)?
+        )
+    };
+    ($($entries:tt)*) => {
+        $crate::conf!(@pairs [] $($entries)*)
+    };
+}
+
+/// Embeds a confee file in the binary as the defaults of a Conf. The file is parsed at compile time, so
+/// a malformed line fails the build, and nothing is read or parsed at run time. The path is relative to
+/// the file invoking the macro, as with include_str!. The delimiter defaults to `:`
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::Conf;
+/// use confee::conf_embed;
+///
+/// let conf = Conf::from(conf_embed!("../../examples/example.conf"));
+/// assert_eq!(conf["port"], "8080");
+/// ```
+#[macro_export]
+macro_rules! conf_embed {
+    ($path:expr) => {
+        $crate::conf_embed!($path, ':')
+    };
+    ($path:expr, $delim:expr) => {{
+        const SOURCE: &str = include_str!($path);
+        const PAIRS: [(&str, &str); $crate::conf::embed::count(SOURCE, $delim)] =
+            $crate::conf::embed::parse(SOURCE, $delim);
+        $crate::conf::embed::to_owned(PAIRS)
+    }};
+}
+
+/// Counts the (key, value) pairs in src
+pub const fn count(src: &str, delim: char) -> usize {
+    let bytes = src.as_bytes();
+    let mut count = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let (line, next) = line_at(bytes, pos);
+        if pair(line, delim).is_some() {
+            count += 1;
+        }
+        pos = next;
+    }
+    count
+}
+
+/// Parses the N (key, value) pairs in src, in order
+pub const fn parse<const N: usize>(src: &str, delim: char) -> [(&str, &str); N] {
+    let bytes = src.as_bytes();
+    let mut pairs = [("", ""); N];
+    let mut i = 0;
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let (line, next) = line_at(bytes, pos);
+        if let Some(pair) = pair(line, delim) {
+            pairs[i] = pair;
+            i += 1;
+        }
+        pos = next;
+    }
+    pairs
+}
+
+pub fn key_value(key: &str, value: impl Display) -> (String, String) {
+    (key.to_string(), value.to_string())
+}
+
+/// Same as key_value, for a key given as the parts of a dotted path
+pub fn path_value(parts: &[&str], value: impl Display) -> (String, String) {
+    (parts.join("."), value.to_string())
+}
+
+pub fn to_owned<const N: usize>(pairs: [(&str, &str); N]) -> [(String, String); N] {
+    pairs.map(|(k, v)| (k.to_string(), v.to_string()))
+}
+
+// Returns the line starting at start, without its newline, and where the next one starts
+const fn line_at(bytes: &[u8], start: usize) -> (&[u8], usize) {
+    let mut end = start;
+    while end < bytes.len() && bytes[end] != b'\n' {
+        end += 1;
+    }
+    let (head, _) = bytes.split_at(end);
+    let (_, line) = head.split_at(start);
+    (line, end + 1)
+}
+
+const fn pair(line: &[u8], delim: char) -> Option<(&str, &str)> {
+    let trimmed = line.trim_ascii();
+    if trimmed.is_empty() || trimmed[0] == b'#' {
+        return None;
+    }
+    let mut buf = [0; 4];
+    let delim = delim.encode_utf8(&mut buf).as_bytes();
+    let mut i = 0;
+    while i + delim.len() <= line.len() {
+        if starts_with(line, i, delim) {
+            let (key, rest) = line.split_at(i);
+            let (_, value) = rest.split_at(delim.len());
+            return Some((to_str(key.trim_ascii()), to_str(value.trim_ascii())));
+        }
+        i += 1;
+    }
+    panic!("No delimiter found in a line of the embedded configuration");
+}
+
+const fn starts_with(line: &[u8], at: usize, prefix: &[u8]) -> bool {
+    let mut i = 0;
+    while i < prefix.len() {
+        if line[at + i] != prefix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn to_str(bytes: &[u8]) -> &str {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(_) => panic!("The embedded configuration is not valid UTF-8"),
+    }
+}
@@ -0,0 +1,235 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+/// Error returned by confee when updating, reading or editing a configuration fails
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConfError {
+    /// Reading the configuration file failed. The underlying io::Error is kept, so callers can tell a
+    /// missing file apart from one they aren't allowed to read
+    #[cfg(feature = "std")]
+    Io {
+        /// The file that couldn't be read
+        file: String,
+        /// What went wrong
+        source: std::io::Error,
+    },
+    /// A line holds neither a pair, a comment nor nothing at all
+    MissingDelimiter {
+        /// Where the line is, counting from 1
+        line_no: usize,
+        /// The line as written
+        line: String,
+    },
+    /// The key isn't one of the Conf's
+    UnknownKey {
+        /// The key asked for, or found in the configuration in strict mode
+        key: String,
+        /// Where the key was found, counting from 1, when it came from a line of the configuration
+        line_no: Option<usize>,
+    },
+    /// A file sets the key more than once, see Conf::with_duplicates
+    DuplicateKey {
+        /// The key set again
+        key: String,
+        /// Where the key was set again, counting from 1, when known
+        line_no: Option<usize>,
+    },
+    /// A value couldn't be parsed into the type asked for, or as written in the configuration, e.g. a quoted
+    /// value missing its closing quote
+    Parse {
+        /// The key holding the value
+        key: String,
+        /// The value as written
+        value: String,
+        /// Why parsing failed
+        message: String,
+        /// Where the value was found, counting from 1, when it came from a line of the configuration
+        line_no: Option<usize>,
+    },
+    /// A value failed the validation rules of its key
+    Invalid {
+        /// The key the value was meant for
+        key: String,
+        /// The value as written, masked for secret keys
+        value: String,
+        /// Why the value is invalid
+        message: String,
+        /// Where the value was found, counting from 1, when it came from a line of the configuration
+        line_no: Option<usize>,
+    },
+    /// The configuration isn't valid UTF-8. The underlying Utf8Error is kept, telling where the invalid
+    /// bytes start
+    Utf8 {
+        /// The line holding the invalid bytes, counting from 1
+        line_no: usize,
+        /// What went wrong
+        source: core::str::Utf8Error,
+    },
+    /// A lower-trust layer tried to set a locked key
+    Locked {
+        /// The locked key
+        key: String,
+        /// The layer that was refused, such as `env MYAPP_PORT` or `arg --port`
+        layer: String,
+    },
+    /// Required keys were found neither in the configuration nor by prompting
+    MissingKeys {
+        /// The missing keys, with their descriptions if they have one
+        keys: Vec<String>,
+    },
+    /// Any other failure, such as a bad signature, described by its message
+    Other(String),
+}
+
+impl ConfError {
+    /// Gets the kind of the underlying io::Error, if reading the file is what failed
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Conf;
+    /// use std::io::ErrorKind;
+    ///
+    /// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+    /// match conf.with_file("does-not-exist.conf").update() {
+    ///     Err(e) if e.io_kind() == Some(ErrorKind::NotFound) => println!("no file, using the defaults"),
+    ///     Err(e) => panic!("{}", e),
+    ///     Ok(_) => {}
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn io_kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            ConfError::Io { source, .. } => Some(source.kind()),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ConfError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            ConfError::Io { source, .. } => Display::fmt(source, f),
+            ConfError::MissingDelimiter { line_no, line } => {
+                write!(f, "No delimiter found in line {}: {}", line_no, line)
+            }
+            ConfError::UnknownKey { key, line_no: None } => write!(f, "Unknown key: {}", key),
+            ConfError::UnknownKey { key, line_no: Some(line_no) } => {
+                write!(f, "Unknown key in line {}: {}", line_no, key)
+            }
+            ConfError::DuplicateKey { key, line_no: None } => write!(f, "Duplicate key: {}", key),
+            ConfError::DuplicateKey { key, line_no: Some(line_no) } => {
+                write!(f, "Duplicate key in line {}: {}", line_no, key)
+            }
+            ConfError::Parse { key, value, message, line_no: None } => {
+                write!(f, "Can't parse value {:?} of key {}: {}", value, key, message)
+            }
+            ConfError::Parse { key, value, message, line_no: Some(line_no) } => {
+                write!(f, "Can't parse value {:?} of key {} in line {}: {}", value, key, line_no, message)
+            }
+            ConfError::Invalid { key, value, message, line_no: None } => {
+                write!(f, "Invalid value {:?} for key {}: {}", value, key, message)
+            }
+            ConfError::Invalid { key, value, message, line_no: Some(line_no) } => {
+                write!(f, "Invalid value {:?} for key {} in line {}: {}", value, key, line_no, message)
+            }
+            ConfError::Utf8 { line_no, source } => write!(f, "Invalid UTF-8 in line {}: {}", line_no, source),
+            ConfError::Locked { key, layer } => write!(f, "Key {} is locked and can't be set from {}", key, layer),
+            ConfError::MissingKeys { keys } => write!(f, "Missing required keys: {}", keys.join(", ")),
+            ConfError::Other(message) => f.write_str(message),
+        }
+    }
+}
+
+impl core::error::Error for ConfError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "std")]
+            ConfError::Io { source, .. } => Some(source),
+            ConfError::Utf8 { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl ConfError {
+    // The error for contents that aren't valid UTF-8, on the line where the invalid bytes start
+    pub(crate) fn utf8(contents: &[u8], source: core::str::Utf8Error) -> Self {
+        let before = &contents[..source.valid_up_to()];
+        ConfError::Utf8 {
+            line_no: before.iter().filter(|&&b| b == b'\n').count() + 1,
+            source,
+        }
+    }
+}
+
+impl From<String> for ConfError {
+    fn from(message: String) -> Self {
+        ConfError::Other(message)
+    }
+}
+
+/// Serialized as an object with the kind of error, its message, and the fields of the variant
+#[cfg(feature = "serde")]
+impl serde::Serialize for ConfError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use alloc::string::ToString;
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("ConfError", 5)?;
+        match self {
+            #[cfg(feature = "std")]
+            ConfError::Io { file, source } => {
+                state.serialize_field("kind", "io")?;
+                state.serialize_field("file", file)?;
+                state.serialize_field("io_kind", &alloc::format!("{:?}", source.kind()))?;
+            }
+            ConfError::MissingDelimiter { line_no, line } => {
+                state.serialize_field("kind", "missing_delimiter")?;
+                state.serialize_field("line_no", line_no)?;
+                state.serialize_field("line", line)?;
+            }
+            ConfError::UnknownKey { key, line_no } => {
+                state.serialize_field("kind", "unknown_key")?;
+                state.serialize_field("key", key)?;
+                state.serialize_field("line_no", line_no)?;
+            }
+            ConfError::DuplicateKey { key, line_no } => {
+                state.serialize_field("kind", "duplicate_key")?;
+                state.serialize_field("key", key)?;
+                state.serialize_field("line_no", line_no)?;
+            }
+            ConfError::Parse { key, value, line_no, .. } => {
+                state.serialize_field("kind", "parse")?;
+                state.serialize_field("key", key)?;
+                state.serialize_field("value", value)?;
+                state.serialize_field("line_no", line_no)?;
+            }
+            ConfError::Invalid { key, value, line_no, .. } => {
+                state.serialize_field("kind", "invalid")?;
+                state.serialize_field("key", key)?;
+                state.serialize_field("value", value)?;
+                state.serialize_field("line_no", line_no)?;
+            }
+            ConfError::Utf8 { line_no, .. } => {
+                state.serialize_field("kind", "utf8")?;
+                state.serialize_field("line_no", line_no)?;
+            }
+            ConfError::Locked { key, layer } => {
+                state.serialize_field("kind", "locked")?;
+                state.serialize_field("key", key)?;
+                state.serialize_field("layer", layer)?;
+            }
+            ConfError::MissingKeys { keys } => {
+                state.serialize_field("kind", "missing_keys")?;
+                state.serialize_field("keys", keys)?;
+            }
+            ConfError::Other(_) => state.serialize_field("kind", "other")?,
+        }
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
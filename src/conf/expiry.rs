@@ -0,0 +1,42 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use std::time::{Duration, Instant};
+
+/// Time to live of keys whose values go stale, such as leased credentials, and when each was last read
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Expiry {
+    ttls: BTreeMap<String, Duration>,
+    refreshed: BTreeMap<String, Instant>,
+}
+
+impl Expiry {
+    pub(crate) fn set(&mut self, key: &str, ttl: Duration) {
+        self.ttls.insert(key.to_string(), ttl);
+    }
+
+    /// Restarts key's clock, if it has a time to live
+    pub(crate) fn refresh(&mut self, key: &str) {
+        if self.ttls.contains_key(key) {
+            self.refreshed.insert(key.to_string(), Instant::now());
+        }
+    }
+
+    /// A key with a time to live is stale until an update first reads it, and again once the time runs out
+    pub(crate) fn is_stale(&self, key: &str) -> bool {
+        let Some(ttl) = self.ttls.get(key) else {
+            return false;
+        };
+        self.refreshed
+            .get(key)
+            .is_none_or(|refreshed| refreshed.elapsed() >= *ttl)
+    }
+
+    pub(crate) fn stale(&self) -> Vec<&str> {
+        self.ttls
+            .keys()
+            .filter(|key| self.is_stale(key))
+            .map(String::as_str)
+            .collect()
+    }
+}
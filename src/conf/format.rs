@@ -0,0 +1,184 @@
+mod dotenv;
+mod ini;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "toml")]
+mod toml;
+mod yaml;
+
+use super::{parse_pairs, ConfError};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+/// Formats a Conf can be exported to, and that (key, value) pairs can be read from. Nested structures, such
+/// as INI sections, TOML tables or YAML mappings, are flattened into dotted keys like `server.port`, and
+/// arrays into indexed keys like `hosts.0`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+    /// confee's own `[key][delim] [value]` line format, using the Conf's delimiter
+    Confee,
+    /// `KEY=value` lines, as read by dotenv libraries, docker and systemd, with optional `export` prefixes and
+    /// quoted values that may span several lines
+    DotEnv,
+    /// `key = value` lines grouped under `[section]` headers
+    Ini,
+    /// TOML, reading it requires the `toml` feature
+    Toml,
+    /// JSON, reading it requires the `json` feature
+    Json,
+    /// YAML block mappings and sequences, plain and quoted scalars and block scalars. Anchors, tags and
+    /// multiple documents aren't supported
+    Yaml,
+}
+
+impl Format {
+    /// Guesses the format of a file from its name, e.g. `app.toml`, `.env` or `.env.local`
+    #[cfg(feature = "std")]
+    pub fn from_extension(file_name: &str) -> Option<Format> {
+        let name = Path::new(file_name).file_name()?.to_str()?;
+        if name == ".env" || name.starts_with(".env.") {
+            return Some(Format::DotEnv);
+        }
+        match Path::new(name).extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "conf" | "confee" => Some(Format::Confee),
+            "env" => Some(Format::DotEnv),
+            "ini" => Some(Format::Ini),
+            "toml" => Some(Format::Toml),
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Parses every (key, value) pair in input, in order. delim only applies to the confee format
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Format;
+    /// let pairs = Format::Ini.parse("[server]\nport = 8080\n", ':').unwrap();
+    /// assert_eq!(pairs, [("server.port".to_string(), "8080".to_string())]);
+    /// ```
+    pub fn parse(self, input: &str, delim: char) -> Result<Vec<(String, String)>, ConfError> {
+        let pairs = match self {
+            Format::Confee => {
+                return Ok(parse_pairs(input, delim)?
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect())
+            }
+            Format::DotEnv => dotenv::parse(input),
+            Format::Ini => ini::parse(input),
+            #[cfg(feature = "toml")]
+            Format::Toml => toml::parse(input),
+            #[cfg(not(feature = "toml"))]
+            Format::Toml => Err("Reading TOML requires the toml feature".to_string()),
+            #[cfg(feature = "json")]
+            Format::Json => json::parse(input),
+            #[cfg(not(feature = "json"))]
+            Format::Json => Err("Reading JSON requires the json feature".to_string()),
+            Format::Yaml => yaml::parse(input),
+        };
+        Ok(pairs?)
+    }
+
+    /// Renders pairs, which are expected to be sorted by key
+    pub(crate) fn write(self, pairs: &[(&str, &str)], delim: &str) -> String {
+        match self {
+            Format::Confee => pairs
+                .iter()
+                .map(|(k, v)| format!("{}{} {}\n", k, delim, v))
+                .collect(),
+            Format::DotEnv => dotenv::write(pairs),
+            Format::Ini => ini::write(pairs),
+            Format::Yaml => yaml::write(pairs),
+            Format::Toml => pairs
+                .iter()
+                .map(|(k, v)| format!("{} = {}\n", toml_key(k), quote(v)))
+                .collect(),
+            Format::Json => {
+                if pairs.is_empty() {
+                    return "{}\n".to_string();
+                }
+                let fields: Vec<String> = pairs
+                    .iter()
+                    .map(|(k, v)| format!("  {}: {}", quote(k), quote(v)))
+                    .collect();
+                format!("{{\n{}\n}}\n", fields.join(",\n"))
+            }
+        }
+    }
+}
+
+/// Parses a JSON Merge Patch document into flattened keys, where null, which removes a member, gives None
+#[cfg(feature = "json")]
+pub(crate) fn parse_merge_patch(input: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    json::parse_nullable(input)
+}
+
+/// Quotes s as a string literal that is valid in JSON, TOML and YAML
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() && (c as u32) < 0x80 => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Dotted keys are quoted as a whole, so flattening the TOML again gives back the same key
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        quote(key)
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Format::Confee => "confee",
+            Format::DotEnv => "dotenv",
+            Format::Ini => "ini",
+            Format::Toml => "toml",
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+        })
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "confee" | "conf" => Ok(Format::Confee),
+            "dotenv" | "env" => Ok(Format::DotEnv),
+            "ini" => Ok(Format::Ini),
+            "toml" => Ok(Format::Toml),
+            "json" => Ok(Format::Json),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            _ => Err(format!("Unknown format: {}", s)),
+        }
+    }
+}
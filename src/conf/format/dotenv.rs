@@ -0,0 +1,113 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+pub(super) fn parse(input: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs = Vec::new();
+    let mut lines = input.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line
+            .strip_prefix("export")
+            .filter(|rest| rest.starts_with([' ', '\t']))
+            .map_or(line, str::trim_start);
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("No '=' found in line: {}", line))?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(format!("No key found in line: {}", line));
+        }
+        // Quoted values may span several lines
+        let mut value = value.trim().to_string();
+        while is_unterminated(&value) {
+            let next = lines
+                .next()
+                .ok_or_else(|| format!("Unterminated quoted value: {}", value))?;
+            value.push('\n');
+            value.push_str(next);
+        }
+        pairs.push((key.to_string(), unquote(&value)?));
+    }
+    Ok(pairs)
+}
+
+fn is_unterminated(value: &str) -> bool {
+    let Some(quote) = value.chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+        return false;
+    };
+    let mut chars = value[1..].chars();
+    while let Some(c) = chars.next() {
+        if c == quote {
+            return false;
+        }
+        if c == '\\' && quote == '"' {
+            chars.next();
+        }
+    }
+    true
+}
+
+fn unquote(value: &str) -> Result<String, String> {
+    let unterminated = || format!("Unterminated quoted value: {}", value);
+    if let Some(rest) = value.strip_prefix('\'') {
+        let end = rest.find('\'').ok_or_else(unterminated)?;
+        return Ok(rest[..end].to_string());
+    }
+    let Some(rest) = value.strip_prefix('"') else {
+        // Unquoted values may carry a trailing comment
+        let value = value.find(" #").map_or(value, |i| &value[..i]);
+        return Ok(value.trim_end().to_string());
+    };
+    let mut unquoted = String::with_capacity(rest.len());
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Ok(unquoted),
+            '\\' => match chars.next().ok_or_else(unterminated)? {
+                'n' => unquoted.push('\n'),
+                'r' => unquoted.push('\r'),
+                't' => unquoted.push('\t'),
+                c => unquoted.push(c),
+            },
+            c => unquoted.push(c),
+        }
+    }
+    Err(unterminated())
+}
+
+pub(super) fn write(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}\n", k, quote(v)))
+        .collect()
+}
+
+/// Double quotes value unless it is made only of characters every env-file reader takes literally
+fn quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-./:@,+%".contains(c));
+    if plain {
+        return value.to_string();
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' | '\\' | '$' | '`' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
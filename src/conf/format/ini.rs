@@ -0,0 +1,52 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+pub(super) fn parse(input: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs = Vec::new();
+    let mut section = String::new();
+    for line in input.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name
+                .strip_suffix(']')
+                .ok_or_else(|| format!("Malformed section header: {}", line))?;
+            section = name.trim().to_string();
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("No '=' found in line: {}", line))?;
+        let key = if section.is_empty() {
+            key.trim().to_string()
+        } else {
+            format!("{}.{}", section, key.trim())
+        };
+        pairs.push((key, value.trim().to_string()));
+    }
+    Ok(pairs)
+}
+
+/// Keys without a dot come first, the rest are grouped into sections named after their first segment
+pub(super) fn write(pairs: &[(&str, &str)]) -> String {
+    let mut out = String::new();
+    for (key, value) in pairs.iter().filter(|(k, _)| !k.contains('.')) {
+        out.push_str(&format!("{} = {}\n", key, value));
+    }
+    let mut section = None;
+    for (key, value) in pairs.iter().filter(|(k, _)| k.contains('.')) {
+        let (name, key) = key.split_once('.').unwrap_or_default();
+        if section != Some(name) {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(&format!("[{}]\n", name));
+            section = Some(name);
+        }
+        out.push_str(&format!("{} = {}\n", key, value));
+    }
+    out
+}
@@ -0,0 +1,39 @@
+use serde_json::Value;
+
+pub(super) fn parse(input: &str) -> Result<Vec<(String, String)>, String> {
+    Ok(parse_nullable(input)?
+        .into_iter()
+        .map(|(k, v)| (k, v.unwrap_or_default()))
+        .collect())
+}
+
+/// Same as parse, but null values come out as None rather than as empty strings
+pub(super) fn parse_nullable(input: &str) -> Result<Vec<(String, Option<String>)>, String> {
+    let value: Value = serde_json::from_str(input).map_err(|e| e.to_string())?;
+    if !value.is_object() {
+        return Err("Expected a JSON object at the top level".to_string());
+    }
+    let mut pairs = Vec::new();
+    flatten(String::new(), &value, &mut pairs);
+    Ok(pairs)
+}
+
+fn flatten(key: String, value: &Value, pairs: &mut Vec<(String, Option<String>)>) {
+    let child = |name: &str| {
+        if key.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", key, name)
+        }
+    };
+    match value {
+        Value::Object(map) => map.iter().for_each(|(k, v)| flatten(child(k), v, pairs)),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .for_each(|(i, v)| flatten(child(&i.to_string()), v, pairs)),
+        Value::String(s) => pairs.push((key, Some(s.clone()))),
+        Value::Null => pairs.push((key, None)),
+        other => pairs.push((key, Some(other.to_string()))),
+    }
+}
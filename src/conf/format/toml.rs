@@ -0,0 +1,24 @@
+use ::toml::{Table, Value};
+
+pub(super) fn parse(input: &str) -> Result<Vec<(String, String)>, String> {
+    let table: Table = input.parse().map_err(|e: ::toml::de::Error| e.to_string())?;
+    let mut pairs = Vec::new();
+    for (k, v) in &table {
+        flatten(k.clone(), v, &mut pairs);
+    }
+    Ok(pairs)
+}
+
+fn flatten(key: String, value: &Value, pairs: &mut Vec<(String, String)>) {
+    match value {
+        Value::Table(table) => table
+            .iter()
+            .for_each(|(k, v)| flatten(format!("{}.{}", key, k), v, pairs)),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .for_each(|(i, v)| flatten(format!("{}.{}", key, i), v, pairs)),
+        Value::String(s) => pairs.push((key, s.clone())),
+        other => pairs.push((key, other.to_string())),
+    }
+}
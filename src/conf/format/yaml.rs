@@ -0,0 +1,319 @@
+use super::{quote, toml_key};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+pub(super) fn parse(input: &str) -> Result<Vec<(String, String)>, String> {
+    let raw: Vec<&str> = input.lines().collect();
+    let lines = raw
+        .iter()
+        .enumerate()
+        .filter_map(|(no, line)| {
+            let text = strip_comment(line).trim_end();
+            let content = text.trim_start();
+            if content.is_empty() || content == "---" || content == "..." {
+                return None;
+            }
+            Some(Line {
+                no,
+                indent: text.len() - content.len(),
+                text: content,
+            })
+        })
+        .collect();
+    let mut parser = Parser {
+        raw,
+        lines,
+        pos: 0,
+        pairs: Vec::new(),
+    };
+    if let Some(first) = parser.lines.first() {
+        parser.block(first.indent, "")?;
+    }
+    if let Some(line) = parser.lines.get(parser.pos) {
+        return Err(format!("Unexpected indentation in line {}", line.no + 1));
+    }
+    Ok(parser.pairs)
+}
+
+/// A line holding something, with its indentation and without a trailing comment
+struct Line<'a> {
+    no: usize,
+    indent: usize,
+    text: &'a str,
+}
+
+struct Parser<'a> {
+    raw: Vec<&'a str>,
+    lines: Vec<Line<'a>>,
+    pos: usize,
+    pairs: Vec<(String, String)>,
+}
+
+impl<'a> Parser<'a> {
+    // A mapping or a sequence whose entries are indented by indent
+    fn block(&mut self, indent: usize, prefix: &str) -> Result<(), String> {
+        if is_item(self.lines[self.pos].text) {
+            self.sequence(indent, prefix)
+        } else {
+            self.mapping(indent, prefix)
+        }
+    }
+
+    fn mapping(&mut self, indent: usize, prefix: &str) -> Result<(), String> {
+        while let Some(line) = self.lines.get(self.pos) {
+            if line.indent < indent {
+                break;
+            }
+            let no = line.no + 1;
+            if line.indent > indent || is_item(line.text) {
+                return Err(format!("Unexpected indentation in line {}", no));
+            }
+            let (key, value) = split_key(line.text).ok_or_else(|| format!("Expected key: value in line {}", no))?;
+            let key = join(prefix, &scalar(key, no)?);
+            self.pos += 1;
+            self.value(indent, key, value, no)?;
+        }
+        Ok(())
+    }
+
+    fn sequence(&mut self, indent: usize, prefix: &str) -> Result<(), String> {
+        let mut index = 0;
+        while let Some(line) = self.lines.get(self.pos) {
+            if line.indent < indent || (line.indent == indent && !is_item(line.text)) {
+                break;
+            }
+            let no = line.no + 1;
+            if line.indent > indent {
+                return Err(format!("Unexpected indentation in line {}", no));
+            }
+            let key = join(prefix, &index.to_string());
+            let rest = line.text[1..].trim_start();
+            if is_item(rest) || (split_key(rest).is_some() && !rest.starts_with(['"', '\''])) {
+                // A collection starting on the item's line: read it as if it started on a line of its own
+                let indent = indent + line.text.len() - rest.len();
+                self.lines[self.pos] = Line {
+                    no: line.no,
+                    indent,
+                    text: rest,
+                };
+                self.block(indent, &key)?;
+            } else {
+                self.pos += 1;
+                self.value(indent, key, rest, no)?;
+            }
+            index += 1;
+        }
+        Ok(())
+    }
+
+    // What follows a key or an item marker, at indent, on line no
+    fn value(&mut self, indent: usize, key: String, value: &str, no: usize) -> Result<(), String> {
+        if value.starts_with(['|', '>']) {
+            let block = self.block_scalar(indent, value, no)?;
+            self.pairs.push((key, block));
+            return Ok(());
+        }
+        if let Some(items) = value.strip_prefix('[') {
+            let items = items.strip_suffix(']').ok_or_else(|| format!("Unterminated sequence in line {}", no))?;
+            for (i, item) in flow_items(items, no)?.into_iter().enumerate() {
+                self.pairs.push((join(&key, &i.to_string()), scalar(item, no)?));
+            }
+            return Ok(());
+        }
+        if let Some(entries) = value.strip_prefix('{') {
+            let entries = entries.strip_suffix('}').ok_or_else(|| format!("Unterminated mapping in line {}", no))?;
+            for entry in flow_items(entries, no)? {
+                let (k, v) = split_key(entry).ok_or_else(|| format!("Expected key: value in line {}", no))?;
+                self.pairs.push((join(&key, &scalar(k, no)?), scalar(v, no)?));
+            }
+            return Ok(());
+        }
+        if !value.is_empty() {
+            self.pairs.push((key, scalar(value, no)?));
+            return Ok(());
+        }
+        match self.lines.get(self.pos) {
+            Some(next) if next.indent > indent => self.block(next.indent, &key),
+            // Sequences may sit at the same indentation as the key they belong to
+            Some(next) if next.indent == indent && is_item(next.text) => self.sequence(indent, &key),
+            _ => {
+                self.pairs.push((key, String::new()));
+                Ok(())
+            }
+        }
+    }
+
+    // A `|` literal or `>` folded block scalar, more indented than indent, following line no
+    fn block_scalar(&mut self, indent: usize, header: &str, no: usize) -> Result<String, String> {
+        let folded = header.starts_with('>');
+        let chomping = header[1..].trim_start_matches(|c: char| c.is_ascii_digit());
+        let mut body: Vec<&str> = Vec::new();
+        let mut block_indent = None;
+        let mut last = no - 1;
+        for (i, line) in self.raw.iter().enumerate().skip(no) {
+            let content = line.trim_start();
+            if content.is_empty() {
+                body.push("");
+                continue;
+            }
+            let line_indent = line.len() - content.len();
+            if line_indent <= indent {
+                break;
+            }
+            let block_indent = *block_indent.get_or_insert(line_indent);
+            body.push(line.get(block_indent..).unwrap_or(content));
+            last = i;
+        }
+        body.truncate(last + 1 - no);
+        while self.lines.get(self.pos).is_some_and(|line| line.no <= last) {
+            self.pos += 1;
+        }
+        let mut text = String::new();
+        for (i, line) in body.iter().enumerate() {
+            if i > 0 {
+                // Folding turns a line break into a space, unless blank lines follow it, which then stand for
+                // the breaks
+                if !folded || body[i - 1].is_empty() {
+                    text.push('\n');
+                } else if !line.is_empty() {
+                    text.push(' ');
+                }
+            }
+            text.push_str(line);
+        }
+        match chomping {
+            "" if !body.is_empty() => text.push('\n'),
+            "+" => text.push('\n'),
+            "" | "-" => {}
+            _ => return Err(format!("Malformed block scalar header in line {}", no)),
+        }
+        Ok(text)
+    }
+}
+
+fn is_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+// Splits `key: value` at the first colon outside quotes that ends the line or is followed by a space
+fn split_key(text: &str) -> Option<(&str, &str)> {
+    if text.starts_with(['[', '{']) {
+        return None;
+    }
+    let mut quote = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (None, '"' | '\'') if i == 0 => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (None, ':') if chars.peek().is_none_or(|(_, next)| next.is_whitespace()) => {
+                return Some((text[..i].trim_end(), text[i + 1..].trim_start()));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Cuts a comment off line: a `#` outside quotes that starts the line or follows whitespace
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut prev = ' ';
+    let mut chars = line.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (None, '"' | '\'') if prev.is_whitespace() || matches!(prev, '[' | '{' | ',' | ':' | '-') => {
+                quote = Some(c)
+            }
+            (Some(q), c) if c == q => quote = None,
+            (Some('"'), '\\') => {
+                chars.next();
+            }
+            (None, '#') if prev.is_whitespace() => return &line[..i],
+            _ => {}
+        }
+        prev = c;
+    }
+    line
+}
+
+// The items of a flow collection, without the brackets. Nested collections aren't supported
+fn flow_items(items: &str, no: usize) -> Result<Vec<&str>, String> {
+    if items.contains(['[', '{']) {
+        return Err(format!("Nested flow collections aren't supported, in line {}", no));
+    }
+    if items.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(items.split(',').map(str::trim).collect())
+}
+
+fn scalar(value: &str, no: usize) -> Result<String, String> {
+    let unterminated = || format!("Unterminated quoted value in line {}", no);
+    if let Some(rest) = value.strip_prefix('\'') {
+        let inner = rest.strip_suffix('\'').ok_or_else(unterminated)?;
+        return Ok(inner.replace("''", "'"));
+    }
+    let Some(rest) = value.strip_prefix('"') else {
+        return Ok(match value {
+            "~" | "null" | "Null" | "NULL" => String::new(),
+            plain => plain.to_string(),
+        });
+    };
+    let inner = rest.strip_suffix('"').ok_or_else(unterminated)?;
+    let mut unescaped = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('0') => unescaped.push('\0'),
+            Some('u') => {
+                let code: String = chars.by_ref().take(4).collect();
+                let c = u32::from_str_radix(&code, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("Malformed escape in line {}", no))?;
+                unescaped.push(c);
+            }
+            Some(c @ ('"' | '\\' | '/' | ' ')) => unescaped.push(c),
+            _ => return Err(format!("Malformed escape in line {}", no)),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Nests dotted keys back into mappings, quoting every value
+pub(super) fn write(pairs: &[(&str, &str)]) -> String {
+    let mut pairs: Vec<(Vec<&str>, &str)> = pairs.iter().map(|(k, v)| (k.split('.').collect(), *v)).collect();
+    pairs.sort();
+    let mut out = String::new();
+    let mut parents: &[&str] = &[];
+    for (segments, value) in &pairs {
+        let (leaf, new_parents) = segments.split_last().unwrap_or((&"", &[]));
+        let common = parents.iter().zip(new_parents).take_while(|(a, b)| a == b).count();
+        for (depth, segment) in new_parents.iter().enumerate().skip(common) {
+            out.push_str(&format!("{}{}:\n", "  ".repeat(depth), toml_key(segment)));
+        }
+        out.push_str(&format!("{}{}: {}\n", "  ".repeat(new_parents.len()), toml_key(leaf), quote(value)));
+        parents = new_parents;
+    }
+    out
+}
@@ -0,0 +1,76 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::{String, ToString};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+/// A value a key held until it was changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Change {
+    /// The value the key held
+    pub value: String,
+    /// What changed it: the file name, `source`, `string`, `patch`, `overrides`, `prompt` or `set`
+    pub source: String,
+    /// When it was changed, only known with the `std` feature
+    #[cfg(feature = "std")]
+    pub time: SystemTime,
+}
+
+/// The last few values of each key, oldest first
+#[derive(Debug, Clone)]
+pub(crate) struct History {
+    limit: usize,
+    source: String,
+    changes: BTreeMap<String, VecDeque<Change>>,
+}
+
+impl History {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            source: String::new(),
+            changes: BTreeMap::new(),
+        }
+    }
+
+    /// Sets what the changes recorded from now on come from
+    pub(crate) fn set_source(&mut self, source: &str) {
+        self.source.clear();
+        self.source.push_str(source);
+    }
+
+    pub(crate) fn record(&mut self, key: &str, old: &str) {
+        if !self.changes.contains_key(key) {
+            self.changes.insert(key.to_string(), VecDeque::new());
+        }
+        let Some(changes) = self.changes.get_mut(key) else {
+            return;
+        };
+        if changes.len() == self.limit {
+            changes.pop_front();
+        }
+        changes.push_back(Change {
+            value: old.to_string(),
+            source: self.source.clone(),
+            #[cfg(feature = "std")]
+            time: SystemTime::now(),
+        });
+    }
+
+    pub(crate) fn get(&self, key: &str) -> impl Iterator<Item = &Change> {
+        self.changes.get(key).into_iter().flatten()
+    }
+
+    pub(crate) fn pop(&mut self, key: &str) -> Option<Change> {
+        self.changes.get_mut(key)?.pop_back()
+    }
+
+    /// Puts back the changes recorded for key as they were, e.g. before a failed update
+    pub(crate) fn restore(&mut self, key: &str, changes: VecDeque<Change>) {
+        if changes.is_empty() {
+            self.changes.remove(key);
+        } else {
+            self.changes.insert(key.to_string(), changes);
+        }
+    }
+}
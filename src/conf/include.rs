@@ -0,0 +1,57 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The files an include directive names, relative to dir unless absolute: the file itself, or when the
+/// last component of path holds `*` or `?` wildcards, every file it matches, in name order. A pattern in a
+/// missing directory matches nothing
+pub(crate) fn resolve(dir: &Path, path: &str) -> io::Result<Vec<PathBuf>> {
+    let path = dir.join(path);
+    let Some(pattern) = path.file_name().and_then(|name| name.to_str()).filter(|name| name.contains(['*', '?']))
+    else {
+        return Ok(vec![path]);
+    };
+    let parent = path.parent().unwrap_or(dir);
+    let entries = match parent.read_dir() {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let matched = entry.file_name().to_str().is_some_and(|name| matches(pattern, name));
+        if matched && entry.file_type()?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// Whether name matches pattern, where `*` stands for any run of characters and `?` for any one
+fn matches(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+    // Where the last `*` was, and the part of name it has taken so far, to backtrack to on a mismatch
+    let (mut p, mut n, mut star) = (0, 0, None);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
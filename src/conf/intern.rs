@@ -0,0 +1,45 @@
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use core::borrow::Borrow;
+
+#[cfg(feature = "std")]
+type Pool = std::collections::HashSet<Interned>;
+#[cfg(not(feature = "std"))]
+type Pool = alloc::collections::BTreeSet<Interned>;
+
+/// Pool of shared values, so that a value repeated across many keys is allocated once
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Interner(Pool);
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Interned(Arc<String>);
+
+// String hashes and orders exactly like str, so lookups by &str find the matching entry
+impl Borrow<str> for Interned {
+    fn borrow(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl Interner {
+    /// Returns the pooled copy of value, adding it to the pool first if needed
+    pub(crate) fn intern(&mut self, value: impl AsRef<str>) -> Arc<String> {
+        let value = value.as_ref();
+        if let Some(Interned(pooled)) = self.0.get(value) {
+            return Arc::clone(pooled);
+        }
+        let pooled = Arc::new(value.to_string());
+        self.0.insert(Interned(Arc::clone(&pooled)));
+        pooled
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+    }
+
+    /// Drops pooled values no key refers to anymore
+    pub(crate) fn prune(&mut self) {
+        self.0.retain(|Interned(pooled)| Arc::strong_count(pooled) > 1);
+    }
+}
@@ -0,0 +1,77 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Expands the `${name}` references in templates, by key. A name naming another template takes its
+/// expansion, any other name is looked up through lookup. `$${` stands for a literal `${`
+pub(crate) struct Expander<'a, F> {
+    templates: &'a BTreeMap<String, String>,
+    lookup: F,
+    max_len: usize,
+    done: BTreeMap<&'a str, String>,
+    stack: Vec<&'a str>,
+}
+
+impl<'a, F: Fn(&str) -> Option<String>> Expander<'a, F> {
+    pub(crate) fn new(templates: &'a BTreeMap<String, String>, lookup: F, max_len: usize) -> Self {
+        Self {
+            templates,
+            lookup,
+            max_len,
+            done: BTreeMap::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Expands every template, or gives the key that couldn't be expanded and why
+    pub(crate) fn expand_all(mut self) -> Result<Vec<(String, String)>, (String, String)> {
+        for key in self.templates.keys() {
+            self.expand(key)?;
+        }
+        Ok(self.done.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+    }
+
+    fn expand(&mut self, key: &'a str) -> Result<String, (String, String)> {
+        if let Some(value) = self.done.get(key) {
+            return Ok(value.clone());
+        }
+        let fail = |message: String| (key.to_string(), message);
+        if self.stack.contains(&key) {
+            return Err(fail(format!("Reference cycle: {} -> {}", self.stack.join(" -> "), key)));
+        }
+        self.stack.push(key);
+        let mut rest = self.templates[key].as_str();
+        let mut expanded = String::with_capacity(rest.len());
+        while let Some(i) = rest.find('$') {
+            expanded.push_str(&rest[..i]);
+            rest = &rest[i..];
+            if let Some(after) = rest.strip_prefix("$${") {
+                expanded.push_str("${");
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix("${") {
+                let end = after.find('}').ok_or_else(|| fail("Unterminated reference".to_string()))?;
+                let name = &after[..end];
+                let value = match self.templates.get_key_value(name) {
+                    Some((name, _)) => self.expand(name)?,
+                    None => (self.lookup)(name).ok_or_else(|| fail(format!("Unknown reference ${{{}}}", name)))?,
+                };
+                expanded.push_str(&value);
+                rest = &after[end + 1..];
+            } else {
+                expanded.push('$');
+                rest = &rest[1..];
+            }
+            if expanded.len() > self.max_len {
+                return Err(fail(format!("Expands to more than {} bytes", self.max_len)));
+            }
+        }
+        expanded.push_str(rest);
+        if expanded.len() > self.max_len {
+            return Err(fail(format!("Expands to more than {} bytes", self.max_len)));
+        }
+        self.stack.pop();
+        self.done.insert(key, expanded.clone());
+        Ok(expanded)
+    }
+}
@@ -0,0 +1,78 @@
+use super::{store, Conf};
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::iter::FusedIterator;
+use core::panic::Location;
+
+type Pairs<'a> = store::Iter<'a, String, Arc<String>>;
+
+/// Iterator over the keys and values of a Conf, in no particular order. Created by Conf::iter
+pub struct Iter<'a> {
+    conf: &'a Conf,
+    pairs: Pairs<'a>,
+    location: &'static Location<'static>,
+}
+
+impl<'a> Iter<'a> {
+    pub(crate) fn new(conf: &'a Conf, location: &'static Location<'static>) -> Self {
+        Self {
+            conf,
+            pairs: conf.pairs.iter(),
+            location,
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.pairs.next()?;
+        // Reading an audited key's value through the iterator counts as an access, as get does
+        self.conf.audit(key, self.location);
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pairs.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+impl FusedIterator for Iter<'_> {}
+
+/// Iterator over the keys of a Conf, in no particular order. Created by Conf::keys
+pub struct Keys<'a>(pub(crate) Pairs<'a>);
+
+impl<'a> Iterator for Keys<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key.as_str())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Keys<'_> {}
+impl FusedIterator for Keys<'_> {}
+
+/// Iterator over the values of a Conf, in no particular order. Created by Conf::values
+pub struct Values<'a>(pub(crate) Iter<'a>);
+
+impl<'a> Iterator for Values<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Values<'_> {}
+impl FusedIterator for Values<'_> {}
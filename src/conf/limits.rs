@@ -0,0 +1,105 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+
+/// Caps on what configuration input may hold, for input from less-trusted sources such as user uploads or
+/// remote endpoints. An update breaking any of them fails as a whole, with ConfError::Invalid. The default
+/// sets no limits
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::{Conf, ConfError, Limits};
+/// let mut conf = Conf::from([("motd".to_string(), "".to_string())]);
+/// conf.with_limits(Limits { max_value_len: 80, ..Limits::untrusted() });
+/// let refused = conf.update_from_str("motd: \x1b[2Jgotcha\n");
+/// assert!(matches!(refused, Err(ConfError::Invalid { line_no: Some(1), .. })));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Longest key allowed, in bytes
+    pub max_key_len: usize,
+    /// Longest value allowed, in bytes
+    pub max_value_len: usize,
+    /// Most segments allowed in a dotted key, e.g. 3 for `server.tls.cert`
+    pub max_depth: usize,
+    /// Longest a value may grow to through interpolation, in bytes
+    pub max_expansion: usize,
+    /// Whether keys and values may hold control characters other than tab, such as terminal escapes
+    pub allow_control_chars: bool,
+}
+
+impl Limits {
+    /// Limits suited to untrusted input: keys of up to 256 bytes and 16 levels, values of up to 64 KiB
+    /// expanding to at most 1 MiB, and no control characters
+    pub const fn untrusted() -> Self {
+        Self {
+            max_key_len: 256,
+            max_value_len: 64 * 1024,
+            max_depth: 16,
+            max_expansion: 1024 * 1024,
+            allow_control_chars: false,
+        }
+    }
+
+    /// Longest line that can hold a key and a value within the limits, with room for the delimiter,
+    /// padding and the line ending
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    pub(crate) fn max_line_len(&self) -> u64 {
+        self.max_key_len.saturating_add(self.max_value_len).saturating_add(64) as u64
+    }
+
+    pub(crate) fn check(&self, key: &str, value: &str) -> Result<(), String> {
+        if key.len() > self.max_key_len {
+            return Err(format!("Key longer than {} bytes: {}...", self.max_key_len, truncate(key)));
+        }
+        if value.len() > self.max_value_len {
+            return Err(format!("Value of key {} longer than {} bytes", key, self.max_value_len));
+        }
+        if key.split('.').count() > self.max_depth {
+            return Err(format!("Key nested deeper than {} levels: {}", self.max_depth, key));
+        }
+        if !self.allow_control_chars {
+            if key.chars().any(is_forbidden) {
+                return Err(format!("Control character in key: {}", key.escape_debug()));
+            }
+            if value.chars().any(is_forbidden) {
+                return Err(format!("Control character in value of key: {}", key));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_key_len: usize::MAX,
+            max_value_len: usize::MAX,
+            max_depth: usize::MAX,
+            max_expansion: usize::MAX,
+            allow_control_chars: true,
+        }
+    }
+}
+
+fn is_forbidden(c: char) -> bool {
+    c.is_control() && c != '\t'
+}
+
+/// s as errors repeat it: cut short when it's longer than max bytes
+pub(crate) fn excerpt(s: &str, max: usize) -> String {
+    if s.len() > max {
+        format!("{}...", truncate(s))
+    } else {
+        s.to_string()
+    }
+}
+
+// Keeps error messages short when the key itself is what's too long
+fn truncate(key: &str) -> &str {
+    let end = (0..=32.min(key.len()))
+        .rev()
+        .find(|i| key.is_char_boundary(*i))
+        .unwrap_or_default();
+    &key[..end]
+}
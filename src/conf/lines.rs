@@ -0,0 +1,69 @@
+use super::delim::Delims;
+use alloc::borrow::Cow;
+use core::iter::{Enumerate, Peekable};
+
+/// The lines of a confee file, numbered from 1. With continuations, a pair ending in `\` is joined with the
+/// line after it, and the indented lines following a pair are joined to it with line breaks. A joined line
+/// is numbered as its first one
+pub(crate) struct Lines<'a> {
+    lines: Peekable<Enumerate<core::str::Lines<'a>>>,
+    continuations: Option<(&'a Delims, &'a [char])>,
+}
+
+impl<'a> Lines<'a> {
+    pub(crate) fn new(contents: &'a str) -> Self {
+        Self {
+            lines: contents.lines().enumerate().peekable(),
+            continuations: None,
+        }
+    }
+
+    /// Joins continued lines, telling pairs apart from other lines with delims and comment_chars
+    pub(crate) fn with_continuations(mut self, delims: &'a Delims, comment_chars: &'a [char]) -> Self {
+        self.continuations = Some((delims, comment_chars));
+        self
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = (usize, Cow<'a, str>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (i, line) = self.lines.next()?;
+        let Some((delims, comment_chars)) = self.continuations else {
+            return Some((i + 1, Cow::Borrowed(line)));
+        };
+        if !is_pair(line, delims, comment_chars) {
+            return Some((i + 1, Cow::Borrowed(line)));
+        }
+        let mut line = Cow::Borrowed(line);
+        loop {
+            if let Some(len) = line.trim_end().strip_suffix('\\').map(str::len) {
+                let line = line.to_mut();
+                line.truncate(len);
+                match self.lines.next() {
+                    Some((_, next)) => line.push_str(next.trim_start()),
+                    None => break,
+                }
+            } else if let Some((_, next)) = self.lines.next_if(|(_, next)| continues(next, comment_chars)) {
+                let line = line.to_mut();
+                line.push('\n');
+                line.push_str(next.trim());
+            } else {
+                break;
+            }
+        }
+        Some((i + 1, line))
+    }
+}
+
+pub(crate) fn is_pair(line: &str, delims: &Delims, comment_chars: &[char]) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty() && !trimmed.starts_with(comment_chars) && delims.find(line).is_some()
+}
+
+// Whether line is an indented continuation of the line above: neither blank nor a comment
+pub(crate) fn continues(line: &str, comment_chars: &[char]) -> bool {
+    let trimmed = line.trim_start();
+    line.starts_with(char::is_whitespace) && !trimmed.is_empty() && !trimmed.starts_with(comment_chars)
+}
@@ -0,0 +1,68 @@
+use super::edit::Editor;
+use super::{parse_pairs, ConfError};
+use std::collections::HashMap;
+
+/// Outcome of Conf::merge3
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Merge3 {
+    /// The merged file, which is the user's file with only the lines that had to change rewritten
+    pub contents: String,
+    /// Keys changed both by the user and upstream, in different ways. The user's side is kept for them
+    pub conflicts: Vec<Conflict>,
+}
+
+/// A key both the user and the new defaults changed, each in their own way. None means the key is absent
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Conflict {
+    /// The key in conflict
+    pub key: String,
+    /// Its value in the old defaults
+    pub old_default: Option<String>,
+    /// Its value in the user's file
+    pub user: Option<String>,
+    /// Its value in the new defaults
+    pub new_default: Option<String>,
+}
+
+pub(crate) fn merge3(
+    old_defaults: &str,
+    user_file: &str,
+    new_defaults: &str,
+    delim: char,
+) -> Result<Merge3, ConfError> {
+    let old: HashMap<&str, &str> = parse_pairs(old_defaults, delim)?.into_iter().collect();
+    let user: HashMap<&str, &str> = parse_pairs(user_file, delim)?.into_iter().collect();
+    let new = parse_pairs(new_defaults, delim)?;
+    let new_map: HashMap<&str, &str> = new.iter().copied().collect();
+
+    let mut editor = Editor::from_contents("", user_file, delim);
+    let mut conflicts = Vec::new();
+    let mut keys: Vec<&str> = old.keys().chain(new_map.keys()).copied().collect();
+    // Keys only known to the new defaults are appended in the order they appear there
+    keys.sort_by_key(|key| (new.iter().position(|(k, _)| k == key), *key));
+    keys.dedup();
+    for key in keys {
+        let (o, u, n) = (old.get(key), user.get(key), new_map.get(key));
+        if u == o {
+            // Untouched by the user, so upstream wins, be it a new value or a removal
+            match n {
+                Some(n) if u != Some(n) => editor.set(key, n),
+                None if u.is_some() => editor.remove(key),
+                _ => &mut editor,
+            };
+        } else if n != o && n != u {
+            conflicts.push(Conflict {
+                key: key.to_string(),
+                old_default: o.map(|v| v.to_string()),
+                user: u.map(|v| v.to_string()),
+                new_default: n.map(|v| v.to_string()),
+            });
+        }
+    }
+    Ok(Merge3 {
+        contents: editor.contents(),
+        conflicts,
+    })
+}
@@ -0,0 +1,105 @@
+use super::ConfError;
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use core::fmt::{self, Debug, Formatter};
+use core::time::Duration;
+
+/// Callbacks reporting on the health of a Conf, to be implemented against whatever metrics client the
+/// application already uses, e.g. Prometheus or StatsD. Every method does nothing by default
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::{Conf, ConfMetrics};
+/// use std::sync::atomic::{AtomicU64, Ordering};
+///
+/// #[derive(Default)]
+/// struct Reloads(AtomicU64);
+///
+/// impl ConfMetrics for Reloads {
+///     fn reload(&self) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+/// conf.with_metrics(Reloads::default());
+/// ```
+pub trait ConfMetrics: Send + Sync {
+    /// Called after every successful update
+    fn reload(&self) {}
+    /// Called after every update, successful or not, with how long it took. Not called on targets
+    /// without a clock, such as `no_std` or the browser
+    fn reload_duration(&self, _duration: Duration) {}
+    /// Called when an update is rejected, e.g. because of a malformed line or a bad signature
+    fn validation_failure(&self, _error: &str) {}
+    /// Called after every successful update, with how many keys changed value
+    fn keys_changed(&self, _count: usize) {}
+}
+
+/// Lets a single metrics sink be shared by several Confs
+impl<T: ConfMetrics + ?Sized> ConfMetrics for Arc<T> {
+    fn reload(&self) {
+        (**self).reload()
+    }
+    fn reload_duration(&self, duration: Duration) {
+        (**self).reload_duration(duration)
+    }
+    fn validation_failure(&self, error: &str) {
+        (**self).validation_failure(error)
+    }
+    fn keys_changed(&self, count: usize) {
+        (**self).keys_changed(count)
+    }
+}
+
+/// Metrics set on a Conf, shared with its clones
+#[derive(Clone)]
+pub(crate) struct Metrics(Arc<dyn ConfMetrics>);
+
+impl Metrics {
+    pub(crate) fn new(metrics: impl ConfMetrics + 'static) -> Self {
+        Self(Arc::new(metrics))
+    }
+
+    pub(crate) fn record(&self, result: &Result<usize, ConfError>, duration: Option<Duration>) {
+        if let Some(duration) = duration {
+            self.0.reload_duration(duration);
+        }
+        match result {
+            Ok(changed) => {
+                self.0.reload();
+                self.0.keys_changed(*changed);
+            }
+            Err(e) => self.0.validation_failure(&e.to_string()),
+        }
+    }
+}
+
+impl Debug for Metrics {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("Metrics")
+    }
+}
+
+/// Times an update, where the platform has a clock to do so
+pub(crate) struct Stopwatch {
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn elapsed(&self) -> Option<Duration> {
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        return Some(self.start.elapsed());
+        #[cfg(not(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown")))))]
+        None
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// A key that was read through get or try_get, but that the Conf doesn't hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MissedKey {
+    /// The key that was read
+    pub key: String,
+    /// The closest key the Conf does hold, which is likely what was meant. Only looked up in debug builds
+    pub suggestion: Option<String>,
+}
+
+/// Keys read without existing, at most MAX_MISSES of them. Behind a Mutex, as reads only borrow the Conf;
+/// once full, misses are no longer recorded and the Mutex is no longer taken
+#[derive(Debug, Default)]
+pub(crate) struct Misses {
+    keys: Mutex<HashSet<String>>,
+    full: AtomicBool,
+}
+
+/// How many missed keys are kept, so that code reading keys built at run time can't grow the set unbounded
+const MAX_MISSES: usize = 64;
+
+impl Misses {
+    pub(crate) fn record(&self, key: &str) {
+        if self.full.load(Ordering::Relaxed) {
+            return;
+        }
+        let mut misses = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        if !misses.contains(key) {
+            misses.insert(key.to_string());
+            self.full.store(misses.len() >= MAX_MISSES, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn keys(&self) -> Vec<String> {
+        let misses = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut keys: Vec<String> = misses.iter().cloned().collect();
+        keys.sort();
+        keys
+    }
+}
+
+impl Clone for Misses {
+    fn clone(&self) -> Self {
+        let misses = self.keys.lock().unwrap_or_else(PoisonError::into_inner);
+        Self {
+            keys: Mutex::new(misses.clone()),
+            full: AtomicBool::new(self.full.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Finds the candidate closest to key, if any is close enough to be a plausible typo
+#[cfg(debug_assertions)]
+pub(crate) fn closest<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let max_distance = (key.chars().count() / 3).max(1);
+    candidates
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance between a and b, counted in chars, where swapping two adjacent chars counts as one edit
+#[cfg(debug_assertions)]
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = (a[i - 1] != b[j - 1]) as usize;
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
@@ -0,0 +1,70 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Incremental change to a Conf, as a list of operations applied in order: Some sets a key to a value,
+/// None resets it, along with every key nested under it, e.g. `server.port` under `server`, to its default
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::{Conf, Patch};
+/// let mut conf = Conf::from([
+///     ("port".to_string(), "8080".to_string()),
+///     ("host".to_string(), "localhost".to_string()),
+/// ]);
+/// let mut patch = Patch::new();
+/// patch.set("port", "9090").reset("host");
+/// conf.apply_patch(&patch).unwrap();
+/// assert_eq!(conf["port"], "9090");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Patch(Vec<(String, Option<String>)>);
+
+impl Patch {
+    /// Creates an empty Patch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a JSON Merge Patch (RFC 7386) document. Nested members map to dotted keys, and null resets
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::Patch;
+    /// let patch = Patch::from_merge_patch(r#"{"server": {"port": 9090}, "host": null}"#).unwrap();
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_merge_patch(json: &str) -> Result<Self, super::ConfError> {
+        Ok(Self(super::format::parse_merge_patch(json)?))
+    }
+
+    /// Adds an operation setting key to value
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        self.0.push((key.to_string(), Some(value.to_string())));
+        self
+    }
+
+    /// Adds an operation resetting key to its default
+    pub fn reset(&mut self, key: &str) -> &mut Self {
+        self.0.push((key.to_string(), None));
+        self
+    }
+
+    /// Gets the operations of this Patch, in order
+    pub fn operations(&self) -> &[(String, Option<String>)] {
+        &self.0
+    }
+}
+
+impl FromIterator<(String, Option<String>)> for Patch {
+    fn from_iter<I: IntoIterator<Item = (String, Option<String>)>>(operations: I) -> Self {
+        Self(operations.into_iter().collect())
+    }
+}
+
+impl From<Vec<(String, Option<String>)>> for Patch {
+    fn from(operations: Vec<(String, Option<String>)>) -> Self {
+        Self(operations)
+    }
+}
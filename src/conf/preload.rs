@@ -0,0 +1,151 @@
+use super::delim::Delims;
+#[cfg(feature = "signing")]
+use super::signature;
+use super::{drop_in_files, expand_path, include, quote, split_line, Conf};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use std::path::Path;
+
+/// The files update is about to read, for update_async to read them on a thread of its own: the file or
+/// candidates, the drop-in files and, with includes on, whatever they include, recursively. With a public
+/// key set, the detached signature of each is read too
+pub(crate) struct Preload {
+    files: Vec<String>,
+    dir: Option<String>,
+    syntax: Option<Syntax>,
+    #[cfg(feature = "signing")]
+    signed: bool,
+    // Files whose signature alone is read, such as the one a source is checked against
+    #[cfg(feature = "signing")]
+    signatures: Vec<String>,
+}
+
+// What telling include lines apart takes
+struct Syntax {
+    delims: Delims,
+    comment_chars: Vec<char>,
+    sections: bool,
+    quotes: bool,
+    inline_comments: bool,
+    continuations: bool,
+    case_insensitive: bool,
+}
+
+impl Preload {
+    pub(crate) fn of(conf: &Conf) -> Self {
+        let syntax = conf.includes.then(|| Syntax {
+            delims: conf.delims.clone(),
+            comment_chars: conf.comment_chars.clone(),
+            sections: conf.sections,
+            quotes: conf.quotes,
+            inline_comments: conf.inline_comments,
+            continuations: conf.continuations,
+            case_insensitive: conf.case_insensitive,
+        });
+        Self {
+            files: conf.base_files(),
+            dir: conf.conf_dir.as_deref().map(expand_path),
+            syntax,
+            #[cfg(feature = "signing")]
+            signed: conf.public_key.is_some(),
+            #[cfg(feature = "signing")]
+            signatures: Vec::new(),
+        }
+    }
+
+    /// What update reads besides conf's source: the signature the source is checked against, if any
+    #[cfg_attr(not(feature = "signing"), allow(unused_variables))]
+    pub(crate) fn signature_of(conf: &Conf) -> Self {
+        Self {
+            files: Vec::new(),
+            dir: None,
+            syntax: None,
+            #[cfg(feature = "signing")]
+            signed: false,
+            #[cfg(feature = "signing")]
+            signatures: conf.public_key.iter().map(|_| conf.conf_file_name.clone()).collect(),
+        }
+    }
+
+    /// Reads the files, by name as update opens them. Files that can't be read are left out, for update to
+    /// fail on as it would
+    pub(crate) fn read(self) -> BTreeMap<String, String> {
+        let mut files = self.files.clone();
+        if let Some(dir) = &self.dir {
+            files.extend(drop_in_files(dir).unwrap_or_default());
+        }
+        let mut read = BTreeMap::new();
+        for file in files {
+            self.read_file(file, &mut read);
+        }
+        #[cfg(feature = "signing")]
+        for file in &self.signatures {
+            read_signature(file, &mut read);
+        }
+        read
+    }
+
+    fn read_file(&self, file: String, read: &mut BTreeMap<String, String>) {
+        if read.contains_key(&file) {
+            return;
+        }
+        let Ok(contents) = std::fs::read_to_string(&file) else {
+            return;
+        };
+        let included = match &self.syntax {
+            Some(syntax) => syntax.included(&file, &contents),
+            None => Vec::new(),
+        };
+        #[cfg(feature = "signing")]
+        if self.signed {
+            read_signature(&file, read);
+        }
+        read.insert(file, contents);
+        for file in included {
+            self.read_file(file, read);
+        }
+    }
+}
+
+impl Syntax {
+    // The files the include lines of contents name, resolved as Conf::include does
+    fn included(&self, file: &str, contents: &str) -> Vec<String> {
+        let dir = Path::new(file).parent().unwrap_or(Path::new(""));
+        let lines = self.continuations.then(|| (self.delims.clone(), self.comment_chars.clone()));
+        let mut in_section = false;
+        let mut files = Vec::new();
+        for (line_no, line) in Conf::lines(contents, lines.as_ref()) {
+            if let Some(name) = line.trim().strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                if self.sections {
+                    in_section = !name.trim().is_empty();
+                    continue;
+                }
+            }
+            let Ok(Some((key, value))) = split_line(line_no, &line, &self.delims, &self.comment_chars) else {
+                continue;
+            };
+            if key != "include" && !(self.case_insensitive && key.eq_ignore_ascii_case("include")) {
+                continue;
+            }
+            let Ok(value) = quote::written(value, self.quotes, self.inline_comments, &self.comment_chars) else {
+                continue;
+            };
+            let path = if self.quotes { quote::unquote(value).unwrap_or_default() } else { Cow::Borrowed(value) };
+            if in_section || path.is_empty() {
+                continue;
+            }
+            let resolved = include::resolve(dir, &expand_path(&path)).unwrap_or_default();
+            files.extend(resolved.iter().map(|file| file.to_string_lossy().into_owned()));
+        }
+        files
+    }
+}
+
+// Reads the detached signature of file, by name as apply_contents looks it up
+#[cfg(feature = "signing")]
+fn read_signature(file: &str, read: &mut BTreeMap<String, String>) {
+    let signature_file = signature::signature_file(file);
+    if let Ok(encoded) = std::fs::read_to_string(&signature_file) {
+        read.insert(signature_file, encoded);
+    }
+}
@@ -0,0 +1,35 @@
+use std::io::{self, IsTerminal, Write};
+
+/// Checks whether there is someone at the terminal to answer prompts
+pub(crate) fn is_interactive() -> bool {
+    io::stdin().is_terminal()
+}
+
+/// Asks for the value of key on the terminal, after its description, and showing default, if any, in
+/// brackets. Secret values are read without echoing them. An empty answer keeps the default, and gives None
+pub(crate) fn ask(
+    key: &str,
+    description: Option<&str>,
+    default: &str,
+    secret: bool,
+) -> Result<Option<String>, String> {
+    if let Some(description) = description {
+        eprintln!("{}", description);
+    }
+    let prompt = if default.is_empty() {
+        format!("{}: ", key)
+    } else {
+        format!("{} [{}]: ", key, default)
+    };
+    let answer = if secret {
+        rpassword::prompt_password(prompt).map_err(|e| e.to_string())?
+    } else {
+        eprint!("{}", prompt);
+        io::stderr().flush().map_err(|e| e.to_string())?;
+        let mut line = String::new();
+        io::stdin().read_line(&mut line).map_err(|e| e.to_string())?;
+        line
+    };
+    let trimmed = answer.trim();
+    Ok((!trimmed.is_empty()).then(|| trimmed.to_string()))
+}
@@ -0,0 +1,101 @@
+use alloc::string::{String, ToString};
+use core::fmt::{self, Display, Formatter};
+
+/// Where the value of a key came from, see Conf::provenance
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Provenance<'a> {
+    /// The key holds its default
+    Default,
+    /// A configuration file or layer, by path
+    File(&'a str),
+    /// An environment variable, by name
+    Env(&'a str),
+    /// A command line argument, by key
+    Arg(&'a str),
+    /// The source set with with_source
+    Source,
+    /// Anything else: `string`, `patch`, `overrides`, `prompt`, `set` or `undo`
+    Other(&'a str),
+}
+
+impl Display for Provenance<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Provenance::Default => f.write_str("default"),
+            Provenance::File(path) => f.write_str(path),
+            Provenance::Env(name) => write!(f, "env {}", name),
+            Provenance::Arg(key) => write!(f, "arg --{}", key),
+            Provenance::Source => f.write_str("source"),
+            Provenance::Other(what) => f.write_str(what),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    File,
+    #[cfg(feature = "std")]
+    Env,
+    #[cfg(feature = "std")]
+    Arg,
+    Source,
+    Other,
+}
+
+/// What the values applied from now on come from, as Conf records it for each key
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Origin {
+    kind: Kind,
+    label: String,
+}
+
+impl Origin {
+    pub(crate) fn file(path: &str) -> Self {
+        Self::new(Kind::File, path.to_string())
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn env(name: &str) -> Self {
+        Self::new(Kind::Env, alloc::format!("env {}", name))
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn arg(key: &str) -> Self {
+        Self::new(Kind::Arg, alloc::format!("arg --{}", key))
+    }
+
+    pub(crate) fn source() -> Self {
+        Self::new(Kind::Source, "source".to_string())
+    }
+
+    pub(crate) fn other(what: &str) -> Self {
+        Self::new(Kind::Other, what.to_string())
+    }
+
+    fn new(kind: Kind, label: String) -> Self {
+        Self { kind, label }
+    }
+
+    /// The origin as Conf::origin reports it, e.g. `env MYAPP_PORT`
+    pub(crate) fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Whether the origin is an override layer, which locked keys refuse
+    #[cfg(feature = "std")]
+    pub(crate) fn is_override(&self) -> bool {
+        matches!(self.kind, Kind::Env | Kind::Arg)
+    }
+
+    pub(crate) fn provenance(&self) -> Provenance<'_> {
+        match self.kind {
+            Kind::File => Provenance::File(&self.label),
+            #[cfg(feature = "std")]
+            Kind::Env => Provenance::Env(&self.label["env ".len()..]),
+            #[cfg(feature = "std")]
+            Kind::Arg => Provenance::Arg(&self.label["arg --".len()..]),
+            Kind::Source => Provenance::Source,
+            Kind::Other => Provenance::Other(&self.label),
+        }
+    }
+}
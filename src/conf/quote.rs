@@ -0,0 +1,91 @@
+use super::strip_inline_comment;
+use alloc::borrow::Cow;
+use alloc::string::String;
+
+// The value as written on its line. A quoted value runs to its closing quote, after which only an inline
+// comment may follow; any other value loses its inline comment, when those are on
+pub(crate) fn written<'a>(
+    value: &'a str,
+    quotes: bool,
+    inline_comments: bool,
+    comment_chars: &[char],
+) -> Result<&'a str, String> {
+    if !quotes || !value.starts_with('"') {
+        return Ok(if inline_comments { strip_inline_comment(value, comment_chars) } else { value });
+    }
+    let mut escaped = false;
+    let end = value.char_indices().skip(1).find_map(|(i, c)| {
+        let end = c == '"' && !escaped;
+        escaped = c == '\\' && !escaped;
+        end.then_some(i + 1)
+    });
+    let Some(end) = end else {
+        return Err(String::from("Unterminated quoted value"));
+    };
+    let rest = value[end..].trim_start();
+    if rest.is_empty() || inline_comments && rest.starts_with(comment_chars) {
+        Ok(&value[..end])
+    } else {
+        Err(alloc::format!("Unexpected {} after quoted value", rest))
+    }
+}
+
+// The value a written one stands for: a quoted value without its quotes and with its escapes replaced.
+// Any other value is kept as is
+pub(crate) fn unquote(value: &str) -> Result<Cow<'_, str>, String> {
+    let Some(inner) = value.strip_prefix('"').and_then(|value| value.strip_suffix('"')).filter(|_| value.len() > 1)
+    else {
+        return Ok(Cow::Borrowed(value));
+    };
+    if !inner.contains('\\') {
+        return Ok(Cow::Borrowed(inner));
+    }
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unquoted.push(c);
+            continue;
+        }
+        unquoted.push(match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some(c @ ('\\' | '"')) => c,
+            Some(c) => return Err(alloc::format!("Unknown escape \\{} in quoted value", c)),
+            None => return Err(String::from("Unterminated escape in quoted value")),
+        });
+    }
+    Ok(Cow::Owned(unquoted))
+}
+
+// value, quoted if it wouldn't read back the same otherwise: when it has whitespace around it, holds a line
+// break, starts with a quote or holds what reads as an inline comment, given the characters starting those
+#[cfg(feature = "std")]
+pub(crate) fn quote<'a>(value: &'a str, comment_chars: &[char]) -> Cow<'a, str> {
+    let needs_quotes = value.trim() != value
+        || value.starts_with('"')
+        || value.contains(['\n', '\r', '\0'])
+        || strip_inline_comment(value, comment_chars).len() != value.len();
+    if !needs_quotes {
+        return Cow::Borrowed(value);
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\0' => quoted.push_str("\\0"),
+            '\\' | '"' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
@@ -0,0 +1,52 @@
+use super::history::Change;
+use super::provenance::Origin;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// What the running update changed, for putting it back should the update fail: each key it changed, as
+/// the key was before. Keys the update sets to what they already hold are left out, so that reloading an
+/// unchanged file allocates nothing
+#[derive(Default)]
+pub(crate) struct Rollback(BTreeMap<String, Held>);
+
+/// A key as it was before the update first changed anything about it
+pub(crate) struct Held {
+    pub(crate) value: Arc<String>,
+    pub(crate) comment: Option<String>,
+    pub(crate) origin: Option<Arc<Origin>>,
+    pub(crate) found: bool,
+    /// The values the history recorded for the key, when keeping one
+    pub(crate) history: Option<VecDeque<Change>>,
+}
+
+impl Rollback {
+    pub(crate) fn holds(&self, key: &str) -> bool {
+        self.0.contains_key(key)
+    }
+
+    pub(crate) fn hold(&mut self, key: &str, held: Held) {
+        self.0.insert(key.to_string(), held);
+    }
+
+    pub(crate) fn take(&mut self) -> BTreeMap<String, Held> {
+        core::mem::take(&mut self.0)
+    }
+
+    /// Forgets what the update changed once it succeeded, wiping the secret values it replaced
+    pub(crate) fn commit(&mut self, secret: impl Fn(&str) -> bool) {
+        #[cfg(feature = "zeroize")]
+        for (key, mut held) in self.take() {
+            if let Some(owned) = Arc::get_mut(&mut held.value).filter(|_| secret(&key)) {
+                owned.zeroize();
+            }
+        }
+        #[cfg(not(feature = "zeroize"))]
+        {
+            let _ = secret;
+            self.0.clear();
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+use core::ops::{Bound, RangeBounds};
+use core::str::FromStr;
+
+/// Checks the values a key is set to. update refuses values that fail the check. Closures taking the value
+/// and returning why it is invalid implement it too
+pub trait Validator: Send + Sync {
+    /// Checks value, returning why it is invalid if it is
+    fn validate(&self, value: &str) -> Result<(), String>;
+}
+
+impl<F> Validator for F
+where
+    F: Fn(&str) -> Result<(), String> + Send + Sync,
+{
+    fn validate(&self, value: &str) -> Result<(), String> {
+        self(value)
+    }
+}
+
+type Check = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// A Validator built from common checks, all of which must pass
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::{Conf, Rule};
+/// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+/// conf.with_rule("port", Rule::new().expect_type::<u16>().range(1..=65535));
+/// assert!(conf.update_from_str("port: 0").is_err());
+/// assert!(conf.update_from_str("port: http").is_err());
+/// assert!(conf.update_from_str("port: 9090").is_ok());
+/// ```
+#[derive(Default)]
+pub struct Rule {
+    checks: Vec<Check>,
+}
+
+impl Rule {
+    /// Creates a Rule accepting every value
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires the value to parse as T
+    pub fn expect_type<T>(self) -> Self
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.check(|value| value.parse::<T>().map(|_| ()).map_err(|e| e.to_string()))
+    }
+
+    /// Requires the value to parse as T and to fall within range
+    pub fn range<T, R>(self, range: R) -> Self
+    where
+        T: FromStr + PartialOrd + Display,
+        T::Err: Display,
+        R: RangeBounds<T> + Send + Sync + 'static,
+    {
+        self.check(move |value| {
+            let parsed = value.parse::<T>().map_err(|e| e.to_string())?;
+            if range.contains(&parsed) {
+                return Ok(());
+            }
+            Err(format!("{} is out of range {}", parsed, describe(&range)))
+        })
+    }
+
+    /// Requires the value to be one of values
+    pub fn one_of(self, values: &[&str]) -> Self {
+        let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        self.check(move |value| {
+            if values.iter().any(|v| v == value) {
+                return Ok(());
+            }
+            Err(format!("Expected one of {}", values.join(", ")))
+        })
+    }
+
+    /// Requires the value not to be empty
+    pub fn non_empty(self) -> Self {
+        self.check(|value| if value.is_empty() { Err("Expected a value".to_string()) } else { Ok(()) })
+    }
+
+    /// Adds a check of its own
+    pub fn check(mut self, check: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static) -> Self {
+        self.checks.push(Box::new(check));
+        self
+    }
+}
+
+impl Validator for Rule {
+    fn validate(&self, value: &str) -> Result<(), String> {
+        self.checks.iter().try_for_each(|check| check(value))
+    }
+}
+
+impl Debug for Rule {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Rule({} checks)", self.checks.len())
+    }
+}
+
+fn describe<T: Display>(range: &impl RangeBounds<T>) -> String {
+    let start = match range.start_bound() {
+        Bound::Included(start) => format!("[{}", start),
+        Bound::Excluded(start) => format!("({}", start),
+        Bound::Unbounded => "(..".to_string(),
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => format!("{}]", end),
+        Bound::Excluded(end) => format!("{})", end),
+        Bound::Unbounded => "..)".to_string(),
+    };
+    format!("{}, {}", start, end)
+}
+
+/// Validators set on a Conf, by key
+#[derive(Default, Clone)]
+pub(crate) struct Validators(BTreeMap<String, Vec<Arc<dyn Validator>>>);
+
+impl Validators {
+    pub(crate) fn add(&mut self, key: &str, validator: impl Validator + 'static) {
+        self.0.entry(key.to_string()).or_default().push(Arc::new(validator));
+    }
+
+    pub(crate) fn validate(&self, key: &str, value: &str) -> Result<(), String> {
+        match self.0.get(key) {
+            Some(validators) => validators.iter().try_for_each(|v| v.validate(value)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Debug for Validators {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
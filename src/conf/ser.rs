@@ -0,0 +1,22 @@
+use super::Conf;
+use serde::{Serialize, Serializer};
+
+/// Serializes Conf as a map of its keys and values, sorted by key. Secret values are written as is, as by
+/// export, so the map can be read back with Deserialize
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(feature = "json")] {
+/// # use confee::conf::Conf;
+/// let conf = Conf::from_defaults([("port", "8080"), ("log", "stdout")]);
+/// let json = serde_json::to_string(&conf).unwrap();
+/// assert_eq!(json, r#"{"log":"stdout","port":"8080"}"#);
+/// assert_eq!(serde_json::from_str::<Conf>(&json).unwrap(), conf);
+/// # }
+/// ```
+impl Serialize for Conf {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.pairs.sorted().into_iter().map(|(key, value)| (key, value.as_str())))
+    }
+}
@@ -0,0 +1,93 @@
+use super::{ChangeSet, Conf, ConfError};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+/// A Conf shared between threads: one thread updates it while any number of others read it. Readers get a
+/// snapshot, an `Arc<Conf>` that stays the same for as long as they hold it, so a request handler sees a
+/// consistent configuration even when a reload happens halfway through. A snapshot answers as the Conf
+/// itself would, aliases, case insensitivity and all
+///
+/// Reads aren't lock-free: the current snapshot sits behind an RwLock, held just long enough to clone an
+/// Arc, and written just long enough to swap one. Updates run outside of it, so readers never wait for a
+/// file to be read, and contention is limited to those swaps. Going lock-free would take a dependency such
+/// as arc-swap, for a cost this small
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::{Conf, SharedConf};
+/// # use std::sync::Arc;
+/// let conf = Conf::from([("port".to_string(), "8080".to_string())]);
+/// let shared = Arc::new(SharedConf::new(conf));
+/// let reader = Arc::clone(&shared);
+/// let handler = std::thread::spawn(move || reader.load().get::<u16>("port"));
+/// shared.modify(|conf| conf.update_from_str("port: 9090"))?;
+/// assert!(matches!(handler.join().unwrap(), Some(8080 | 9090)));
+/// assert_eq!(shared.load()["port"], "9090");
+/// # Ok::<(), confee::conf::ConfError>(())
+/// ```
+#[derive(Debug)]
+pub struct SharedConf {
+    conf: Mutex<Conf>,
+    current: RwLock<Arc<Conf>>,
+}
+
+impl SharedConf {
+    /// Shares conf, which keeps its file, source and other settings for later updates
+    pub fn new(conf: Conf) -> Self {
+        let current = RwLock::new(Arc::new(conf.clone()));
+        Self {
+            conf: Mutex::new(conf),
+            current,
+        }
+    }
+
+    /// Gets a snapshot of the current values. Later updates don't change it; load again to see them
+    pub fn load(&self) -> Arc<Conf> {
+        Arc::clone(&self.current.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Updates the shared Conf and, when that succeeds, hands the new values to readers. Returns the keys
+    /// that changed
+    pub fn update(&self) -> Result<ChangeSet, ConfError> {
+        self.modify(Conf::update)
+    }
+
+    /// Changes the shared Conf through f, e.g. to set a value or apply a patch, then hands the new values to
+    /// readers. f works on a copy, kept only when f succeeds: when it fails, neither the shared Conf nor
+    /// its readers see anything it did
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use confee::conf::{Conf, SharedConf};
+    /// let shared = SharedConf::new(Conf::from_defaults([("port", "8080"), ("host", "")]));
+    /// let failed = shared.modify(|conf| {
+    ///     conf.set("host", "example.com");
+    ///     conf.update_from_str("port: 9090\nbogus line")
+    /// });
+    /// assert!(failed.is_err());
+    /// assert_eq!(shared.load()["host"], "");
+    /// assert_eq!(shared.load()["port"], "8080");
+    /// ```
+    pub fn modify<T, E>(&self, f: impl FnOnce(&mut Conf) -> Result<T, E>) -> Result<T, E> {
+        let mut conf = self.conf.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut copy = conf.clone();
+        // The source can't be copied, so the copy borrows it
+        copy.source = conf.source.take();
+        let result = f(&mut copy);
+        if result.is_err() {
+            conf.source = copy.source.take();
+            return result;
+        }
+        let snapshot = Arc::new(copy.clone());
+        *conf = copy;
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = snapshot;
+        result
+    }
+}
+
+impl From<Conf> for SharedConf {
+    fn from(conf: Conf) -> Self {
+        Self::new(conf)
+    }
+}
@@ -0,0 +1,76 @@
+use super::{ChangeSet, Conf, ConfError};
+use std::fs::File;
+use std::io::{self, ErrorKind, Read};
+use std::os::unix::io::FromRawFd;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::thread;
+
+// Write end of the pipe the signal handler wakes the reloading thread through, -1 until installed
+static PIPE: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn on_sighup(_: libc::c_int) {
+    let byte = 1u8;
+    // SAFETY: write is async-signal-safe, and the pipe is never closed once installed. The write end is
+    // non-blocking, so a full pipe, which already holds a pending reload, drops the byte
+    unsafe {
+        libc::write(PIPE.load(Ordering::Relaxed), (&byte as *const u8).cast(), 1);
+    }
+}
+
+pub(crate) fn install(conf: Arc<RwLock<Conf>>) -> Result<Receiver<Result<ChangeSet, ConfError>>, ConfError> {
+    let os_error = || {
+        let error = io::Error::last_os_error();
+        ConfError::Other(format!("Could not install the SIGHUP handler: {}", error))
+    };
+    let mut fds = [0; 2];
+    // SAFETY: fds has room for the two descriptors pipe writes
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(os_error());
+    }
+    let [read_end, write_end] = fds;
+    // SAFETY: both descriptors were just opened and are owned by nothing else
+    let close = || unsafe {
+        libc::close(read_end);
+        libc::close(write_end);
+    };
+    if PIPE.compare_exchange(-1, write_end, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+        close();
+        return Err(ConfError::Other("A SIGHUP handler is already installed".to_string()));
+    }
+    // SAFETY: the descriptor is valid, and the handler only calls async-signal-safe functions
+    let installed = unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_sighup as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::fcntl(write_end, libc::F_SETFL, libc::O_NONBLOCK) == 0
+            && libc::sigaction(libc::SIGHUP, &action, std::ptr::null_mut()) == 0
+    };
+    if !installed {
+        let error = os_error();
+        PIPE.store(-1, Ordering::SeqCst);
+        close();
+        return Err(error);
+    }
+    // SAFETY: the read end is owned by nothing else from here on
+    let mut pipe = unsafe { File::from_raw_fd(read_end) };
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // Signals arriving while a reload runs pile up in the pipe and are read at once, for a single reload
+        let mut pending = [0u8; 64];
+        loop {
+            match pipe.read(&mut pending) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+            let result = conf.write().unwrap_or_else(PoisonError::into_inner).update();
+            // Nobody listening doesn't stop the reloads
+            let _ = sender.send(result);
+        }
+    });
+    Ok(receiver)
+}
@@ -0,0 +1,51 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use std::fs;
+
+const SIG_EXTENSION: &str = "sig";
+
+/// Signs the contents of a configuration file with an ed25519 secret key, returning the base64 signature
+/// confee expects to find next to the file, e.g. in `app.conf.sig`
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::sign;
+/// let signature = sign(&[7u8; 32], b"port: 8080\n");
+/// std::fs::write(std::env::temp_dir().join("app.conf.sig"), signature).unwrap();
+/// ```
+pub fn sign(secret_key: &[u8; 32], contents: &[u8]) -> String {
+    let signature = SigningKey::from_bytes(secret_key).sign(contents);
+    STANDARD.encode(signature.to_bytes())
+}
+
+/// Returns the path of the detached signature belonging to conf_file_name
+pub(crate) fn signature_file(conf_file_name: &str) -> String {
+    format!("{}.{}", conf_file_name, SIG_EXTENSION)
+}
+
+/// Reads the detached signature of conf_file_name, as verify expects it
+pub(crate) fn read(conf_file_name: &str) -> Result<String, String> {
+    let sig_file_name = signature_file(conf_file_name);
+    fs::read_to_string(&sig_file_name)
+        .map_err(|e| format!("Could not read signature file {}: {}", sig_file_name, e))
+}
+
+/// Verifies contents against encoded, the detached signature of conf_file_name, using the pinned public_key
+pub(crate) fn verify(
+    public_key: &[u8; 32],
+    conf_file_name: &str,
+    encoded: &str,
+    contents: &[u8],
+) -> Result<(), String> {
+    let sig_file_name = signature_file(conf_file_name);
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| format!("Malformed signature in {}", sig_file_name))?;
+    let signature = Signature::from_slice(&bytes)
+        .map_err(|_| format!("Malformed signature in {}", sig_file_name))?;
+    let key = VerifyingKey::from_bytes(public_key).map_err(|_| "Invalid public key".to_string())?;
+    key.verify(contents, &signature)
+        .map_err(|_| format!("Signature verification failed for {}", conf_file_name))
+}
@@ -0,0 +1,57 @@
+use super::ConfError;
+use alloc::boxed::Box;
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::string::ToString;
+use core::fmt::{self, Debug, Formatter};
+
+/// Supplies the contents of a configuration to update, e.g. from flash storage, a network fetch or an
+/// embedded string. Closures returning the contents, or why they couldn't be read, implement it too
+pub trait Source {
+    /// Reads the whole configuration, in the confee format
+    fn read(&mut self) -> Result<String, ConfError>;
+}
+
+impl<F> Source for F
+where
+    F: FnMut() -> Result<String, String>,
+{
+    fn read(&mut self) -> Result<String, ConfError> {
+        self().map_err(ConfError::from)
+    }
+}
+
+/// Reads a std::io::Read to its end
+#[cfg(feature = "std")]
+pub(crate) struct ReaderSource<R>(pub(crate) R);
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> Source for ReaderSource<R> {
+    fn read(&mut self) -> Result<String, ConfError> {
+        let mut contents = String::new();
+        self.0.read_to_string(&mut contents).map_err(|source| ConfError::Io {
+            file: "reader".to_string(),
+            source,
+        })?;
+        Ok(contents)
+    }
+}
+
+/// Source set on a Conf
+pub(crate) struct BoxedSource(Box<dyn Source + Send + Sync>);
+
+impl BoxedSource {
+    pub(crate) fn new(source: impl Source + Send + Sync + 'static) -> Self {
+        Self(Box::new(source))
+    }
+
+    pub(crate) fn read(&mut self) -> Result<String, ConfError> {
+        self.0.read()
+    }
+}
+
+impl Debug for BoxedSource {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("Source")
+    }
+}
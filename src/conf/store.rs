@@ -0,0 +1,216 @@
+#[cfg(feature = "std")]
+use super::PairMap;
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt::{self, Debug, Formatter};
+use core::hash::Hash;
+use core::slice;
+
+/// Past this many keys a hash map beats binary search over a sorted Vec
+#[cfg(feature = "std")]
+const COMPACT_THRESHOLD: usize = 32;
+
+/// Storage for (key, value) pairs. Small configurations, which are the common case, live in a Vec sorted by
+/// key; larger ones in a PairMap. Without the `std` feature there is no map, and the Vec is used throughout
+#[derive(Clone)]
+pub(crate) enum Store<K, V> {
+    Compact(Vec<(K, V)>),
+    #[cfg(feature = "std")]
+    Map(PairMap<K, V>),
+}
+
+impl<K, V> Store<K, V>
+where
+    K: Borrow<str> + Hash + Eq,
+{
+    pub(crate) fn get(&self, key: &str) -> Option<&V> {
+        match self {
+            Store::Compact(pairs) => search(pairs, key).ok().map(|i| &pairs[i].1),
+            #[cfg(feature = "std")]
+            Store::Map(map) => map.get(key),
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        match self {
+            Store::Compact(pairs) => search(pairs, key).ok().map(|i| &mut pairs[i].1),
+            #[cfg(feature = "std")]
+            Store::Map(map) => map.get_mut(key),
+        }
+    }
+
+    /// Inserts or replaces the value of key, switching to a map when past the compact threshold
+    pub(crate) fn insert(&mut self, key: K, value: V) {
+        match self {
+            Store::Compact(pairs) => match search(pairs, key.borrow()) {
+                Ok(i) => pairs[i].1 = value,
+                Err(i) => {
+                    pairs.insert(i, (key, value));
+                    #[cfg(feature = "std")]
+                    if pairs.len() > COMPACT_THRESHOLD {
+                        *self = core::mem::take(pairs).into_iter().collect();
+                    }
+                }
+            },
+            #[cfg(feature = "std")]
+            Store::Map(map) => {
+                map.insert(key, value);
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<V> {
+        match self {
+            Store::Compact(pairs) => search(pairs, key).ok().map(|i| pairs.remove(i).1),
+            #[cfg(feature = "std")]
+            Store::Map(map) => map.remove(key),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Store::Compact(pairs) => pairs.len(),
+            #[cfg(feature = "std")]
+            Store::Map(map) => map.len(),
+        }
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        match self {
+            Store::Compact(pairs) => pairs.capacity(),
+            #[cfg(feature = "std")]
+            Store::Map(map) => map.capacity(),
+        }
+    }
+
+    /// Makes room for at least capacity pairs, switching to a map when that is past the compact threshold
+    pub(crate) fn reserve(&mut self, capacity: usize) {
+        match self {
+            #[cfg(feature = "std")]
+            Store::Compact(pairs) if capacity > COMPACT_THRESHOLD => {
+                let mut map = PairMap::with_capacity_and_hasher(capacity, Default::default());
+                map.extend(pairs.drain(..));
+                *self = Store::Map(map);
+            }
+            Store::Compact(pairs) => pairs.reserve(capacity.saturating_sub(pairs.len())),
+            #[cfg(feature = "std")]
+            Store::Map(map) => map.reserve(capacity.saturating_sub(map.len())),
+        }
+    }
+
+    /// Releases spare capacity, switching back to the compact representation when small enough
+    pub(crate) fn shrink_to_fit(&mut self) {
+        match self {
+            Store::Compact(pairs) => pairs.shrink_to_fit(),
+            #[cfg(feature = "std")]
+            Store::Map(map) if map.len() <= COMPACT_THRESHOLD => {
+                *self = core::mem::take(map).into_iter().collect();
+                self.shrink_to_fit();
+            }
+            #[cfg(feature = "std")]
+            Store::Map(map) => map.shrink_to_fit(),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> Iter<'_, K, V> {
+        match self {
+            Store::Compact(pairs) => Iter::Compact(pairs.iter()),
+            #[cfg(feature = "std")]
+            Store::Map(map) => Iter::Map(map.iter()),
+        }
+    }
+
+    /// The pairs sorted by key, for output that mustn't depend on how they are stored
+    pub(crate) fn sorted(&self) -> Vec<(&K, &V)> {
+        let mut pairs: Vec<(&K, &V)> = self.iter().collect();
+        if matches!(self, Store::Compact(_)) {
+            return pairs;
+        }
+        pairs.sort_unstable_by(|(a, _), (b, _)| Borrow::<str>::borrow(*a).cmp(Borrow::<str>::borrow(*b)));
+        pairs
+    }
+
+    pub(crate) fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        match self {
+            Store::Compact(pairs) => IterMut::Compact(pairs.iter_mut()),
+            #[cfg(feature = "std")]
+            Store::Map(map) => IterMut::Map(map.iter_mut()),
+        }
+    }
+}
+
+fn search<K: Borrow<str>, V>(pairs: &[(K, V)], key: &str) -> Result<usize, usize> {
+    pairs.binary_search_by(|(k, _)| k.borrow().cmp(key))
+}
+
+/// Later duplicates win, matching HashMap::from
+impl<K, V> FromIterator<(K, V)> for Store<K, V>
+where
+    K: Borrow<str> + Hash + Eq,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut pairs: Vec<(K, V)> = iter.into_iter().collect();
+        #[cfg(feature = "std")]
+        if pairs.len() > COMPACT_THRESHOLD {
+            return Store::Map(pairs.into_iter().collect());
+        }
+        pairs.reverse();
+        pairs.sort_by(|(a, _), (b, _)| a.borrow().cmp(b.borrow()));
+        pairs.dedup_by(|(a, _), (b, _)| a == b);
+        Store::Compact(pairs)
+    }
+}
+
+impl<K, V> Debug for Store<K, V>
+where
+    K: Borrow<str> + Hash + Eq + Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_map().entries(self.sorted()).finish()
+    }
+}
+
+pub(crate) enum Iter<'a, K, V> {
+    Compact(slice::Iter<'a, (K, V)>),
+    #[cfg(feature = "std")]
+    Map(std::collections::hash_map::Iter<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Iter::Compact(iter) => iter.next().map(|(k, v)| (k, v)),
+            #[cfg(feature = "std")]
+            Iter::Map(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Iter::Compact(iter) => iter.size_hint(),
+            #[cfg(feature = "std")]
+            Iter::Map(iter) => iter.size_hint(),
+        }
+    }
+}
+
+pub(crate) enum IterMut<'a, K, V> {
+    Compact(slice::IterMut<'a, (K, V)>),
+    #[cfg(feature = "std")]
+    Map(std::collections::hash_map::IterMut<'a, K, V>),
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            IterMut::Compact(iter) => iter.next().map(|(k, v)| (&*k, v)),
+            #[cfg(feature = "std")]
+            IterMut::Map(iter) => iter.next(),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use super::store::Store;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+
+type Callback = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+/// Callbacks subscribed to changes of single keys, by key
+#[derive(Default, Clone)]
+pub(crate) struct Subscribers(BTreeMap<String, Vec<Callback>>);
+
+impl Subscribers {
+    pub(crate) fn add(&mut self, key: &str, callback: impl Fn(&str, &str) + Send + Sync + 'static) {
+        self.0.entry(key.to_string()).or_default().push(Arc::new(callback));
+    }
+
+    /// The values of the subscribed keys, taken before an update to notify about what it changed
+    pub(crate) fn values(&self, pairs: &Store<String, Arc<String>>) -> Vec<(String, Arc<String>)> {
+        self.0
+            .keys()
+            .filter_map(|key| Some((key.clone(), Arc::clone(pairs.get(key)?))))
+            .collect()
+    }
+
+    /// Calls the callbacks of every key whose value differs from the one it held before
+    pub(crate) fn notify(&self, before: &[(String, Arc<String>)], pairs: &Store<String, Arc<String>>) {
+        for (key, old) in before {
+            let Some(new) = pairs.get(key).filter(|new| new.as_str() != old.as_str()) else {
+                continue;
+            };
+            for callback in self.0.get(key).into_iter().flatten() {
+                callback(old, new);
+            }
+        }
+    }
+}
+
+impl Debug for Subscribers {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
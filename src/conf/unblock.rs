@@ -0,0 +1,50 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+/// Runs f on a thread of its own and resolves to what it returns, so blocking work such as reading files
+/// doesn't hold up an async executor. Works with any executor
+pub(crate) fn unblock<T, F>(f: F) -> Unblock<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let state = Arc::new(Mutex::new(State {
+        result: None,
+        waker: None,
+    }));
+    let shared = Arc::clone(&state);
+    thread::spawn(move || {
+        let result = f();
+        let mut state = shared.lock().unwrap_or_else(PoisonError::into_inner);
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    });
+    Unblock(state)
+}
+
+struct State<T> {
+    result: Option<T>,
+    waker: Option<Waker>,
+}
+
+pub(crate) struct Unblock<T>(Arc<Mutex<State<T>>>);
+
+impl<T> Future for Unblock<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::time::Duration;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Parses durations such as `30s`, `5m`, `1h30m`, `1h 30m` or `1.5h`. A bare number counts seconds
+pub(crate) fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("Empty duration".to_string());
+    }
+    let mut nanos: u128 = 0;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let (number, unit, next) = split_quantity(rest);
+        let scale = match unit {
+            "" if number == s => NANOS_PER_SEC,
+            "ns" => 1,
+            "us" | "µs" => 1_000,
+            "ms" => 1_000_000,
+            "s" | "sec" | "secs" => NANOS_PER_SEC,
+            "m" | "min" | "mins" => 60 * NANOS_PER_SEC,
+            "h" => 60 * 60 * NANOS_PER_SEC,
+            "d" => 24 * 60 * 60 * NANOS_PER_SEC,
+            "w" => 7 * 24 * 60 * 60 * NANOS_PER_SEC,
+            _ => return Err(format!("Unknown duration unit {:?} in {}", unit, s)),
+        };
+        nanos = scale_number(number, scale)
+            .and_then(|part| nanos.checked_add(part))
+            .ok_or_else(|| format!("Malformed duration: {}", s))?;
+        rest = next;
+    }
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).map_err(|_| format!("Duration too long: {}", s))?;
+    Ok(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+}
+
+/// Parses byte sizes such as `512`, `10MB` or `512KiB`, case-insensitively. KB, MB, ... are powers of 1000,
+/// while KiB, MiB, ... and the single letters K, M, ... are powers of 1024
+pub(crate) fn parse_bytes(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (number, unit, rest) = split_quantity(s);
+    if !rest.is_empty() {
+        return Err(format!("Malformed byte size: {}", s));
+    }
+    let scale: u128 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000u128.pow(2),
+        "gb" => 1000u128.pow(3),
+        "tb" => 1000u128.pow(4),
+        "pb" => 1000u128.pow(5),
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        "p" | "pib" => 1 << 50,
+        _ => return Err(format!("Unknown byte size unit {:?} in {}", unit, s)),
+    };
+    let bytes = scale_number(number, scale).ok_or_else(|| format!("Malformed byte size: {}", s))?;
+    u64::try_from(bytes).map_err(|_| format!("Byte size too large: {}", s))
+}
+
+// Splits the number and the unit off the start of s, giving back what follows them
+fn split_quantity(s: &str) -> (&str, &str, &str) {
+    let number_end = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let rest = s[number_end..].trim_start();
+    let unit_end = rest
+        .find(|c: char| c.is_ascii_digit() || c == '.' || c.is_whitespace())
+        .unwrap_or(rest.len());
+    (&s[..number_end], &rest[..unit_end], rest[unit_end..].trim_start())
+}
+
+// Multiplies a decimal number, which may have a fraction, by scale. Digits of the fraction beyond what
+// scale can tell apart are dropped
+fn scale_number(number: &str, scale: u128) -> Option<u128> {
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let whole = if whole.is_empty() { 0 } else { whole.parse::<u128>().ok()? };
+    let mut scaled = whole.checked_mul(scale)?;
+    if !fraction.is_empty() {
+        let fraction = &fraction[..fraction.len().min(18)];
+        let digits = fraction.parse::<u128>().ok()?;
+        scaled = scaled.checked_add(digits * scale / 10u128.pow(fraction.len() as u32))?;
+    }
+    Some(scaled)
+}
@@ -0,0 +1,143 @@
+use super::{ChangeSet, Conf};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+#[cfg(feature = "async")]
+use std::sync::Mutex;
+#[cfg(feature = "async")]
+use std::task::{Poll, Waker};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+/// Handle on a watch started by Conf::watch. Dropping it stops the watch
+#[derive(Debug)]
+pub struct Watcher {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// Stops the watch, waiting for a reload in progress to finish
+    pub fn stop(self) {}
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+// When each file was last modified and how long it is, None for files that don't exist
+type Stamps = Vec<Option<(Option<SystemTime>, u64)>>;
+
+fn stamps(files: &[String]) -> Stamps {
+    files
+        .iter()
+        .map(|file| fs::metadata(file).ok().map(|meta| (meta.modified().ok(), meta.len())))
+        .collect()
+}
+
+pub(crate) fn spawn<F>(conf: Arc<RwLock<Conf>>, interval: Duration, mut on_change: F) -> Watcher
+where
+    F: FnMut(&Conf, &ChangeSet) + Send + 'static,
+{
+    let watched = Arc::clone(&conf);
+    let watched = move || watched.read().unwrap_or_else(PoisonError::into_inner).watched_files();
+    poll(watched, interval, move || {
+        let mut conf = conf.write().unwrap_or_else(PoisonError::into_inner);
+        // A failed update is logged and counted by the Conf itself; the next change gets another try
+        if let Ok(changes) = conf.update() {
+            if !changes.is_empty() {
+                on_change(&conf, &changes);
+            }
+        }
+    })
+}
+
+// Checks the files watched gives every interval, calling on_change whenever they were modified
+fn poll<W, F>(watched: W, interval: Duration, mut on_change: F) -> Watcher
+where
+    W: Fn() -> Vec<String> + Send + 'static,
+    F: FnMut() + Send + 'static,
+{
+    let stop = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::clone(&stop);
+    // Taken before returning, so that a change made right after the watch starts isn't missed
+    let mut last = stamps(&watched());
+    let thread = thread::spawn(move || {
+        loop {
+            thread::park_timeout(interval);
+            if stopped.load(Ordering::Relaxed) {
+                break;
+            }
+            let now = stamps(&watched());
+            if now == last {
+                continue;
+            }
+            last = now;
+            on_change();
+        }
+    });
+    Watcher {
+        stop,
+        thread: Some(thread),
+    }
+}
+
+/// Handle on a watch started by Conf::watch_async, for async code to wait on changes with. Dropping it
+/// stops the watch
+#[cfg(feature = "async")]
+#[derive(Debug)]
+pub struct AsyncWatcher {
+    changes: Arc<Mutex<Changes>>,
+    _watcher: Watcher,
+}
+
+// Whether the files changed since changed last resolved, and the task waiting for them to
+#[cfg(feature = "async")]
+#[derive(Debug, Default)]
+struct Changes {
+    changed: bool,
+    waker: Option<Waker>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncWatcher {
+    /// Resolves once the files have changed since the watch started, or since the last time it resolved.
+    /// Changes made meanwhile resolve it once
+    pub async fn changed(&mut self) {
+        core::future::poll_fn(|cx| {
+            let mut changes = self.changes.lock().unwrap_or_else(PoisonError::into_inner);
+            if core::mem::take(&mut changes.changed) {
+                return Poll::Ready(());
+            }
+            changes.waker = Some(cx.waker().clone());
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+#[cfg(feature = "async")]
+pub(crate) fn spawn_async<W>(watched: W, interval: Duration) -> AsyncWatcher
+where
+    W: Fn() -> Vec<String> + Send + 'static,
+{
+    let changes = Arc::new(Mutex::new(Changes::default()));
+    let notified = Arc::clone(&changes);
+    let watcher = poll(watched, interval, move || {
+        let mut changes = notified.lock().unwrap_or_else(PoisonError::into_inner);
+        changes.changed = true;
+        if let Some(waker) = changes.waker.take() {
+            waker.wake();
+        }
+    });
+    AsyncWatcher {
+        changes,
+        _watcher: watcher,
+    }
+}
@@ -0,0 +1,22 @@
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::Response;
+
+/// Fetches url with the browser's fetch API, returning the body of the response
+pub(crate) async fn fetch(url: &str) -> Result<String, String> {
+    let window = web_sys::window().ok_or_else(|| "No window to fetch from".to_string())?;
+    let response = JsFuture::from(window.fetch_with_str(url)).await.map_err(js_error)?;
+    let response: Response = response.dyn_into().map_err(js_error)?;
+    if !response.ok() {
+        return Err(format!("Fetching {} failed with status {}", url, response.status()));
+    }
+    let text = JsFuture::from(response.text().map_err(js_error)?)
+        .await
+        .map_err(js_error)?;
+    text.as_string()
+        .ok_or_else(|| format!("Response from {} is not text", url))
+}
+
+fn js_error(e: JsValue) -> String {
+    e.as_string().unwrap_or_else(|| format!("{:?}", e))
+}
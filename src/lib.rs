@@ -1,4 +1,13 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 /// conf is a module for parsing simple configuration files
 /// and updating user set defaults. 
 /// 
-pub mod conf;
\ No newline at end of file
+pub mod conf;
+
+/// test holds helpers for testing code that uses confee, such as throwaway configuration files and scripted
+/// sources. Requires the `testing` feature
+#[cfg(feature = "testing")]
+pub mod test;
@@ -0,0 +1,143 @@
+use crate::conf::{ConfError, Source};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// A configuration file in the temporary directory, written from a string and deleted on drop
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::Conf;
+/// use confee::test::TempConfig;
+///
+/// let file = TempConfig::new("port: 9090\n");
+/// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+/// conf.with_file(file.path()).update().unwrap();
+/// assert_eq!(conf["port"], "9090");
+/// ```
+#[derive(Debug)]
+pub struct TempConfig {
+    path: PathBuf,
+    path_str: String,
+}
+
+impl TempConfig {
+    /// Writes contents to a new file with the `.conf` extension
+    pub fn new(contents: &str) -> Self {
+        Self::with_extension("conf", contents)
+    }
+
+    /// Writes contents to a new file with the given extension, e.g. `toml` to exercise format detection
+    pub fn with_extension(extension: &str, contents: &str) -> Self {
+        let name = format!(
+            "confee-{}-{}.{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            extension
+        );
+        let path = std::env::temp_dir().join(name);
+        if let Err(e) = fs::write(&path, contents) {
+            panic!("Could not write {}: {}", path.display(), e);
+        }
+        let path_str = path.to_string_lossy().into_owned();
+        Self { path, path_str }
+    }
+
+    /// Gets the path of the file, as with_file expects it
+    pub fn path(&self) -> &str {
+        &self.path_str
+    }
+
+    /// Replaces the contents of the file, e.g. to test a reload
+    pub fn write(&self, contents: &str) {
+        if let Err(e) = fs::write(&self.path, contents) {
+            panic!("Could not write {}: {}", self.path_str, e);
+        }
+    }
+}
+
+impl Drop for TempConfig {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// A Source handing out scripted contents, one per read, for testing reload logic. Clones share the script,
+/// so a test can keep one to push further loads and count reads after handing the other to a Conf. Once
+/// the script runs out, reads fail
+///
+/// # Examples
+///
+/// ```
+/// # use confee::conf::Conf;
+/// use confee::test::MockSource;
+///
+/// let source = MockSource::new(["port: 9090\n", "port: 9091\n"]);
+/// let mut conf = Conf::from([("port".to_string(), "8080".to_string())]);
+/// conf.with_source(source.clone());
+/// conf.update().unwrap();
+/// conf.update().unwrap();
+/// assert_eq!(conf["port"], "9091");
+/// source.push_err("connection refused");
+/// assert!(conf.update().is_err());
+/// assert_eq!(source.reads(), 3);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockSource(Arc<Mutex<Script>>);
+
+#[derive(Debug, Default)]
+struct Script {
+    loads: VecDeque<Result<String, String>>,
+    reads: usize,
+}
+
+impl MockSource {
+    /// Creates a MockSource that successively hands out each of loads
+    pub fn new<I, S>(loads: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let source = Self::default();
+        for contents in loads {
+            source.push(contents);
+        }
+        source
+    }
+
+    /// Adds contents to hand out once the loads before them are read
+    pub fn push(&self, contents: impl Into<String>) -> &Self {
+        self.script().loads.push_back(Ok(contents.into()));
+        self
+    }
+
+    /// Adds a failing read, with error as the message
+    pub fn push_err(&self, error: impl Into<String>) -> &Self {
+        self.script().loads.push_back(Err(error.into()));
+        self
+    }
+
+    /// Gets how many times the source has been read
+    pub fn reads(&self) -> usize {
+        self.script().reads
+    }
+
+    fn script(&self) -> std::sync::MutexGuard<'_, Script> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Source for MockSource {
+    fn read(&mut self) -> Result<String, ConfError> {
+        let mut script = self.script();
+        script.reads += 1;
+        let load = script.loads.pop_front();
+        let load = load.unwrap_or_else(|| Err("MockSource has no more loads scripted".to_string()));
+        load.map_err(ConfError::from)
+    }
+}
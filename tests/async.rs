@@ -0,0 +1,86 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use common::{block_on, TempDir};
+use confee::conf::Conf;
+use confee::test::MockSource;
+use std::time::Duration;
+
+#[test]
+fn reads_the_file_drop_ins_and_includes() {
+    let dir = TempDir::new();
+    let file = dir.write("app.conf", "port: 9090\ninclude: extra.conf\n");
+    dir.write("extra.conf", "host: example.com\n");
+    dir.write("conf.d/10-log.conf", "log: stderr\n");
+    let mut conf = Conf::from_defaults([("port", "8080"), ("host", ""), ("log", "stdout")]);
+    conf.with_file(&file).with_includes(true).with_dir(&dir.path("conf.d"));
+    let changes = block_on(conf.update_async()).unwrap();
+    assert_eq!(changes.len(), 3);
+    assert_eq!(conf["port"], "9090");
+    assert_eq!(conf["host"], "example.com");
+    assert_eq!(conf["log"], "stderr");
+    assert_eq!(conf.origin("host"), Some(dir.path("extra.conf").as_str()));
+}
+
+#[test]
+fn reads_the_source() {
+    let source = MockSource::new(["port: 9090\n"]);
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_source(source.clone());
+    block_on(conf.update_async()).unwrap();
+    assert_eq!(conf["port"], "9090");
+    source.push_err("connection refused");
+    assert!(block_on(conf.update_async()).is_err());
+    assert_eq!(conf["port"], "9090");
+    assert_eq!(source.reads(), 2);
+}
+
+#[test]
+fn missing_file_fails() {
+    let dir = TempDir::new();
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_file(&dir.path("missing.conf"));
+    assert!(block_on(conf.update_async()).is_err());
+}
+
+#[test]
+fn watch_async_resolves_on_change() {
+    let dir = TempDir::new();
+    let file = dir.write("app.conf", "port: 9090\n");
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_file(&file);
+    block_on(conf.update_async()).unwrap();
+    let mut watcher = conf.watch_async(Duration::from_millis(10));
+    dir.write("app.conf", "port: 10101\n");
+    block_on(watcher.changed());
+    block_on(conf.update_async()).unwrap();
+    assert_eq!(conf["port"], "10101");
+}
+
+#[cfg(feature = "signing")]
+#[test]
+fn reads_signatures() {
+    // The key pair of the first test vector of RFC 8032
+    const SECRET_KEY: [u8; 32] = [
+        0x9d, 0x61, 0xb1, 0x9d, 0xef, 0xfd, 0x5a, 0x60, 0xba, 0x84, 0x4a, 0xf4, 0x92, 0xec, 0x2c, 0xc4,
+        0x44, 0x49, 0xc5, 0x69, 0x7b, 0x32, 0x69, 0x19, 0x70, 0x3b, 0xac, 0x03, 0x1c, 0xae, 0x7f, 0x60,
+    ];
+    const PUBLIC_KEY: [u8; 32] = [
+        0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07, 0x3a,
+        0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07, 0x51, 0x1a,
+    ];
+    let dir = TempDir::new();
+    let file = dir.write("app.conf", "port: 9090\n");
+    dir.write("app.conf.sig", &confee::conf::sign(&SECRET_KEY, b"port: 9090\n"));
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_file(&file).with_public_key(PUBLIC_KEY);
+    block_on(conf.update_async()).unwrap();
+    assert_eq!(conf["port"], "9090");
+    dir.write("app.conf", "port: 10101\n");
+    assert!(block_on(conf.update_async()).is_err());
+    assert_eq!(conf["port"], "9090");
+    conf.with_source(MockSource::new(["port: 9090\n", "port: 10101\n"]));
+    block_on(conf.update_async()).unwrap();
+    assert!(block_on(conf.update_async()).is_err());
+}
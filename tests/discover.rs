@@ -0,0 +1,41 @@
+#![cfg(all(unix, not(target_os = "macos")))]
+// Sets environment variables, so kept to a test binary of its own
+
+mod common;
+
+use common::TempDir;
+use confee::conf::{Conf, ConfError};
+use std::io::ErrorKind;
+
+#[test]
+fn discover_prefers_the_user_file() {
+    let home = TempDir::new();
+    let system = TempDir::new();
+    std::env::set_var("HOME", home.path(""));
+    std::env::set_var("XDG_CONFIG_HOME", home.path("xdg"));
+    std::env::set_var("XDG_CONFIG_DIRS", format!("relative:{}", system.path("")));
+
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    let missing = conf.discover("myapp").update().unwrap_err();
+    assert_eq!(missing.io_kind(), Some(ErrorKind::NotFound));
+    let ConfError::Io { file, .. } = missing else { unreachable!() };
+    let candidates = [
+        home.path("xdg/myapp/config"),
+        home.path(".config/myapp/config"),
+        system.path("myapp/config"),
+        "/etc/myapp/config".to_string(),
+    ];
+    assert_eq!(file, candidates.join(", "));
+    assert_eq!(conf["port"], "8080");
+
+    let system_file = system.write("myapp/config", "port: 80\n");
+    conf.discover("myapp").update().unwrap();
+    assert_eq!(conf["port"], "80");
+    assert_eq!(conf.file(), &system_file);
+
+    home.write(".config/myapp/config", "port: 8181\n");
+    let user_file = home.write("xdg/myapp/config", "port: 9090\n");
+    conf.discover("myapp").update().unwrap();
+    assert_eq!(conf["port"], "9090");
+    assert_eq!(conf.file(), &user_file);
+}
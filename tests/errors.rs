@@ -0,0 +1,27 @@
+use confee::conf::{Conf, ConfError};
+use std::error::Error;
+
+#[test]
+fn invalid_utf8_tells_the_line() {
+    let mut conf = Conf::from_defaults([("port", "8080"), ("host", "")]);
+    let error = conf.update_from_bytes(b"port: 9090\nhost: \xff\n").unwrap_err();
+    assert!(matches!(error, ConfError::Utf8 { line_no: 2, .. }));
+    assert!(error.source().is_some_and(|source| source.is::<std::str::Utf8Error>()));
+    assert_eq!(conf["port"], "8080");
+}
+
+#[test]
+fn override_without_equals_names_it() {
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    let error = conf.apply_overrides(["port 9090"]).unwrap_err();
+    assert!(matches!(error, ConfError::Parse { ref key, line_no: None, .. } if key == "port 9090"));
+    assert_eq!(conf["port"], "8080");
+}
+
+#[test]
+fn flag_without_value_names_its_key() {
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    let error = conf.with_str("").with_args(["myapp", "--port"]).update().unwrap_err();
+    assert!(matches!(error, ConfError::Parse { ref key, .. } if key == "port"));
+    assert_eq!(error.to_string(), "Can't parse value \"\" of key port: Missing value after --port");
+}
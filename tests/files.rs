@@ -0,0 +1,80 @@
+mod common;
+
+use common::TempDir;
+use confee::conf::Conf;
+use std::io::ErrorKind;
+
+#[test]
+fn first_existing_candidate_is_read() {
+    let dir = TempDir::new();
+    let local = dir.path("local.conf");
+    let user = dir.write("user.conf", "port: 9090\n");
+    let system = dir.write("system.conf", "port: 80\nlog: syslog\n");
+    let mut conf = Conf::from_defaults([("port", "8080"), ("log", "stdout")]);
+    conf.with_files([&local, &user, &system]).update().unwrap();
+    assert_eq!(conf["port"], "9090");
+    assert_eq!(conf["log"], "stdout");
+    assert_eq!(conf.file(), &user);
+    assert_eq!(conf.loaded_files(), [user]);
+}
+
+#[test]
+fn missing_candidates_are_not_found() {
+    let dir = TempDir::new();
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    let missing = conf.with_files([dir.path("a.conf"), dir.path("b.conf")]).update().unwrap_err();
+    assert_eq!(missing.io_kind(), Some(ErrorKind::NotFound));
+    assert_eq!(conf["port"], "8080");
+    assert!(conf.loaded_files().is_empty());
+}
+
+#[test]
+fn later_layers_override_earlier_ones() {
+    let dir = TempDir::new();
+    let system = dir.write("system.conf", "port: 80\nlog: syslog\n");
+    let user = dir.write("user.conf", "port: 9090\n");
+    let local = dir.path("local.conf");
+    let mut conf = Conf::from_defaults([("port", "8080"), ("log", "stdout")]);
+    conf.with_layers([&system, &user, &local]).update().unwrap();
+    assert_eq!(conf["port"], "9090");
+    assert_eq!(conf["log"], "syslog");
+    assert_eq!(conf.origin("port"), Some(user.as_str()));
+    assert_eq!(conf.origin("log"), Some(system.as_str()));
+    assert_eq!(conf.loaded_files(), [user.as_str(), system.as_str()]);
+}
+
+#[test]
+fn failed_layer_keeps_the_loaded_files() {
+    let dir = TempDir::new();
+    let system = dir.write("system.conf", "port: 80\n");
+    let user = dir.write("user.conf", "port: 9090\n");
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_layers([&system, &user]).update().unwrap();
+    dir.write("user.conf", "port 9191\n");
+    assert!(conf.update().is_err());
+    assert_eq!(conf["port"], "9090");
+    assert_eq!(conf.loaded_files(), [user.as_str(), system.as_str()]);
+}
+
+#[test]
+fn drop_ins_apply_in_name_order_after_the_file() {
+    let dir = TempDir::new();
+    let file = dir.write("app.conf", "port: 80\nlog: syslog\nworkers: 2\n");
+    dir.write("conf.d/20-log.conf", "log: file\n");
+    dir.write("conf.d/10-port.conf", "port: 9090\nlog: stderr\n");
+    dir.write("conf.d/README", "port 1\n");
+    let mut conf = Conf::from_defaults([("port", "8080"), ("log", "stdout"), ("workers", "1")]);
+    conf.with_file(&file).with_dir(&dir.path("conf.d")).update().unwrap();
+    assert_eq!(conf["port"], "9090");
+    assert_eq!(conf["log"], "file");
+    assert_eq!(conf["workers"], "2");
+    assert_eq!(conf.origin("log"), Some(dir.path("conf.d/20-log.conf").as_str()));
+}
+
+#[test]
+fn missing_drop_in_directory_holds_no_files() {
+    let dir = TempDir::new();
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_str("port: 9090").with_dir(&dir.path("conf.d")).update().unwrap();
+    assert_eq!(conf["port"], "9090");
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::TempDir;
+use confee::conf::{Conf, Provenance};
+
+#[test]
+fn secret_marked_in_another_case_is_masked() {
+    let mut conf = Conf::from_defaults([("Password", "hunter2"), ("user", "admin")]);
+    conf.with_case_insensitive(true).mark_secret("PASSWORD");
+    for key in ["Password", "password", "PASSWORD"] {
+        assert!(conf.is_secret(key));
+    }
+    assert!(!format!("{}", conf).contains("hunter2"));
+    assert!(!format!("{:?}", conf).contains("hunter2"));
+    assert!(!conf.to_string_redacted().contains("hunter2"));
+}
+
+#[test]
+fn secret_marked_before_ignoring_case_is_masked() {
+    let mut conf = Conf::from_defaults([("Password", "hunter2")]);
+    conf.mark_secret("PASSWORD").with_case_insensitive(true);
+    assert!(conf.is_secret("password"));
+    assert!(!format!("{:?}", conf).contains("hunter2"));
+}
+
+#[test]
+fn keys_match_whatever_their_case() {
+    let mut conf = Conf::from_defaults([("Port", "8080"), ("host", "")]);
+    conf.with_case_insensitive(true)
+        .describe("PORT", "Listening port")
+        .require("HOST")
+        .mark_audited("port");
+    conf.update_from_str("# Public\nport: 9090\nHOST: example.com\n").unwrap();
+    assert_eq!(conf.description("port"), Some("Listening port"));
+    assert_eq!(conf.comment("PORT"), Some("Public"));
+    assert!(conf.is_required("host"));
+    assert!(conf.missing_keys().is_empty());
+    assert_eq!(conf.origin("PORT"), Some("string"));
+    assert_eq!(conf.provenance("pORT"), Some(Provenance::Other("string")));
+}
+
+#[test]
+fn aliases_reach_the_key() {
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.alias("listen_port", "port").update_from_str("listen_port: 9090").unwrap();
+    assert_eq!(conf["listen_port"], "9090");
+    assert_eq!(conf.origin("listen_port"), Some("string"));
+}
+
+#[test]
+fn include_lines_match_whatever_their_case() {
+    let dir = TempDir::new();
+    let file = dir.write("app.conf", "INCLUDE: log.conf\n");
+    dir.write("log.conf", "log: stderr\n");
+    let mut conf = Conf::from_defaults([("log", "stdout")]);
+    conf.with_file(&file).with_includes(true).with_case_insensitive(true).update().unwrap();
+    assert_eq!(conf["log"], "stderr");
+}
@@ -0,0 +1,23 @@
+use confee::conf::Conf;
+
+#[test]
+fn only_get_and_try_get_record_misses() {
+    let conf = Conf::from_defaults([("port", "8080")]);
+    let _ = conf.get_or("timeout", 30);
+    let _ = conf.get_or_else("workers", || 4);
+    let _ = &conf["host"];
+    assert!(conf.missed_keys().is_empty());
+    let _: Option<u16> = conf.get("prot");
+    let _ = conf.try_get::<u16>("hots");
+    let missed: Vec<_> = conf.missed_keys().into_iter().map(|missed| missed.key).collect();
+    assert_eq!(missed, ["hots", "prot"]);
+}
+
+#[test]
+fn misses_are_capped() {
+    let conf = Conf::from_defaults([("port", "8080")]);
+    for i in 0..1000 {
+        let _: Option<u16> = conf.get(&format!("key{}", i));
+    }
+    assert_eq!(conf.missed_keys().len(), 64);
+}
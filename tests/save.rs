@@ -0,0 +1,49 @@
+mod common;
+
+use common::TempDir;
+use confee::conf::{Conf, Editor};
+use std::fs;
+
+#[test]
+fn save_writes_values_back() {
+    let dir = TempDir::new();
+    let path = dir.write("app.conf", "# Port\nport: 8080\n");
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_file(&path).update().unwrap();
+    conf.set("port", "9090").save().unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "# Port\nport: 9090\n");
+    let mut reread = Conf::from_defaults([("port", "8080")]);
+    reread.with_file(&path).update().unwrap();
+    assert_eq!(reread["port"], "9090");
+}
+
+#[test]
+fn save_leaves_no_temporary_file() {
+    let dir = TempDir::new();
+    let path = dir.write("app.conf", "port: 8080\n");
+    let mut editor = Editor::open(&path, ':').unwrap();
+    editor.set("port", "9090");
+    editor.save().unwrap();
+    editor.save().unwrap();
+    let names: Vec<_> = fs::read_dir(dir.path("")).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+    assert_eq!(names, ["app.conf"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn save_keeps_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new();
+    let path = dir.write("app.conf", "password: hunter2\n");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+    let mut conf = Conf::from_defaults([("password", "")]);
+    conf.with_file(&path).update().unwrap();
+    conf.set("password", "correct horse").save().unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    let mut editor = Editor::open(&path, ':').unwrap();
+    editor.set("password", "battery staple");
+    editor.save().unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    assert_eq!(fs::read_to_string(&path).unwrap(), "password: battery staple\n");
+}
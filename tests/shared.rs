@@ -0,0 +1,37 @@
+use confee::conf::{Conf, SharedConf};
+use confee::test::MockSource;
+
+#[test]
+fn snapshot_reads_like_the_conf() {
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_case_insensitive(true).alias("listen_port", "port");
+    conf.update_from_str("port: 9090").unwrap();
+    let shared = SharedConf::new(conf);
+    let snapshot = shared.load();
+    assert_eq!(snapshot["Port"], "9090");
+    assert_eq!(snapshot["listen_port"], "9090");
+    assert_eq!(snapshot.get::<u16>("PORT"), Some(9090));
+}
+
+#[test]
+fn failed_modify_publishes_nothing() {
+    let shared = SharedConf::new(Conf::from_defaults([("port", "8080")]));
+    let failed = shared.modify(|conf| conf.with_str("port: 1\nbogus line").update());
+    assert!(failed.is_err());
+    assert_eq!(shared.load()["port"], "8080");
+    shared.modify(|conf| conf.update_from_str("port: 9090")).unwrap();
+    assert_eq!(shared.load()["port"], "9090");
+}
+
+#[test]
+fn update_keeps_the_source() {
+    let source = MockSource::new(["port: 9090\n"]);
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_source(source.clone());
+    let shared = SharedConf::new(conf);
+    shared.update().unwrap();
+    source.push("port: 9091\n");
+    shared.update().unwrap();
+    assert_eq!(shared.load()["port"], "9091");
+    assert_eq!(source.reads(), 2);
+}
@@ -0,0 +1,36 @@
+#![cfg(all(unix, feature = "sighup"))]
+
+use confee::conf::Conf;
+use confee::test::TempConfig;
+use std::process::Command;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn sighup() {
+    let pid = std::process::id().to_string();
+    assert!(Command::new("kill").args(["-HUP", &pid]).status().unwrap().success());
+}
+
+// One test only: a process has one handler, kept until it exits
+#[test]
+fn reloads_on_sighup() {
+    let file = TempConfig::new("port: 9090\n");
+    let mut conf = Conf::from_defaults([("port", "8080"), ("host", "")]);
+    conf.with_file(file.path()).update().unwrap();
+    let conf = Arc::new(RwLock::new(conf));
+    let reloads = Conf::reload_on_sighup(Arc::clone(&conf)).unwrap();
+    assert!(Conf::reload_on_sighup(Arc::clone(&conf)).is_err());
+
+    file.write("port: 10101\n");
+    sighup();
+    let changes = reloads.recv_timeout(TIMEOUT).unwrap().unwrap();
+    assert!(changes.contains("port"));
+    assert_eq!(conf.read().unwrap()["port"], "10101");
+
+    file.write("port: 11111\nhost localhost\n");
+    sighup();
+    assert!(reloads.recv_timeout(TIMEOUT).unwrap().is_err());
+    assert_eq!(conf.read().unwrap()["port"], "10101");
+}
@@ -0,0 +1,60 @@
+use confee::conf::{Conf, ConfError, Limits};
+use confee::test::MockSource;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn failed_update_keeps_values() {
+    let mut conf = Conf::from_defaults([("a", "1"), ("b", "")]);
+    conf.with_limits(Limits::untrusted());
+    let refused = conf.update_from_str("a: 2\nb: \x1b[31mred\n");
+    assert!(matches!(refused, Err(ConfError::Invalid { line_no: Some(2), .. })));
+    assert_eq!(conf["a"], "1");
+    assert_eq!(conf["b"], "");
+}
+
+#[test]
+fn failed_update_keeps_comments_origins_and_history() {
+    let mut conf = Conf::from_defaults([("port", "8080"), ("host", "")]);
+    conf.with_history(4).update_from_str("# Public\nport: 9090\n").unwrap();
+    assert!(conf.update_from_str("# Internal\nport: 9191\nhost b\n").is_err());
+    assert_eq!(conf["port"], "9090");
+    assert_eq!(conf.comment("port"), Some("Public"));
+    assert_eq!(conf.origin("port"), Some("string"));
+    assert_eq!(conf.history("port").len(), 1);
+}
+
+#[test]
+fn failed_reload_keeps_the_last_good_values() {
+    let source = MockSource::new(["port: 9090\n", "port: 9191\nhost b\n", "port: 9191\n"]);
+    let mut conf = Conf::from_defaults([("port", "8080"), ("host", "")]);
+    conf.with_source(source);
+    conf.update().unwrap();
+    assert!(conf.update().is_err());
+    assert_eq!(conf["port"], "9090");
+    let changes = conf.update().unwrap();
+    assert_eq!(changes.get("port").unwrap().to_string(), "port: 9090 -> 9191");
+}
+
+#[test]
+fn failed_patch_keeps_reset_keys() {
+    let mut conf = Conf::from_defaults([("port", "8080"), ("host", "")]);
+    conf.update_from_str("port: 9090").unwrap();
+    conf.with_strict(true);
+    let mut patch = confee::conf::Patch::new();
+    patch.reset("port").set("prot", "1");
+    assert!(conf.apply_patch(&patch).is_err());
+    assert_eq!(conf["port"], "9090");
+    assert_eq!(conf.origin("port"), Some("string"));
+}
+
+#[test]
+fn change_survives_a_failed_update() {
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&changes);
+    let mut conf = Conf::from_defaults([("port", "8080"), ("host", "")]);
+    conf.on_change("port", move |old, new| seen.lock().unwrap().push(format!("{} -> {}", old, new)));
+    assert!(conf.update_from_str("port: 9090\nhost b\n").is_err());
+    assert!(changes.lock().unwrap().is_empty());
+    conf.update_from_str("port: 9090\n").unwrap();
+    assert_eq!(*changes.lock().unwrap(), ["8080 -> 9090"]);
+}
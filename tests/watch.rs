@@ -0,0 +1,54 @@
+#![cfg(feature = "watch")]
+
+use confee::conf::Conf;
+use confee::test::TempConfig;
+use std::sync::mpsc;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+const INTERVAL: Duration = Duration::from_millis(10);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn reloads_when_the_file_changes() {
+    let file = TempConfig::new("port: 9090\n");
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_file(file.path()).update().unwrap();
+    let conf = Arc::new(RwLock::new(conf));
+    let (sender, changes) = mpsc::channel();
+    let _watcher = Conf::watch(Arc::clone(&conf), INTERVAL, move |conf, changes| {
+        sender.send((conf["port"].to_string(), changes.len())).unwrap();
+    });
+    file.write("port: 10101\n");
+    assert_eq!(changes.recv_timeout(TIMEOUT).unwrap(), ("10101".to_string(), 1));
+}
+
+#[test]
+fn failed_reload_keeps_the_values() {
+    let file = TempConfig::new("port: 9090\n");
+    let mut conf = Conf::from_defaults([("port", "8080"), ("host", "")]);
+    conf.with_file(file.path()).update().unwrap();
+    let conf = Arc::new(RwLock::new(conf));
+    let (sender, changes) = mpsc::channel();
+    let _watcher = Conf::watch(Arc::clone(&conf), INTERVAL, move |conf, _| {
+        sender.send(conf["port"].to_string()).unwrap();
+    });
+    file.write("port: 10101\nhost localhost\n");
+    std::thread::sleep(INTERVAL * 20);
+    assert_eq!(conf.read().unwrap()["port"], "9090");
+    file.write("port: 10101\n");
+    assert_eq!(changes.recv_timeout(TIMEOUT).unwrap(), "10101");
+}
+
+#[test]
+fn stops_when_dropped() {
+    let file = TempConfig::new("port: 9090\n");
+    let mut conf = Conf::from_defaults([("port", "8080")]);
+    conf.with_file(file.path()).update().unwrap();
+    let conf = Arc::new(RwLock::new(conf));
+    let watcher = Conf::watch(Arc::clone(&conf), INTERVAL, |_, _| {});
+    watcher.stop();
+    file.write("port: 10101\n");
+    std::thread::sleep(INTERVAL * 10);
+    assert_eq!(conf.read().unwrap()["port"], "9090");
+}